@@ -0,0 +1,222 @@
+//! Helpers for display strings containing ANSI escape sequences.
+
+use std::borrow::Cow;
+use crossterm::style::{Attribute, Color, ContentStyle};
+use crate::text;
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+const RESET: &str = "\x1b[0m";
+
+/// A piece of a display string: either visible text or an escape sequence.
+enum Token<'a> {
+    Char(char),
+    Escape(&'a str),
+}
+
+/// Splits `s` into visible characters and escape sequences.
+fn tokens(s: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        let ch = rest.chars().next()?;
+        if ch != ESC {
+            rest = &rest[ch.len_utf8()..];
+            return Some(Token::Char(ch));
+        }
+        let len = escape_len(rest);
+        let (escape, tail) = rest.split_at(len);
+        rest = tail;
+        Some(Token::Escape(escape))
+    })
+}
+
+/// Returns the byte length of the escape sequence at the start of `s`.
+fn escape_len(s: &str) -> usize {
+    let mut chars = s.char_indices().skip(1);
+    match chars.next() {
+        // CSI: parameters and intermediates, terminated by a byte in 0x40..=0x7e.
+        Some((_, '[')) => chars
+            .find(|&(_, ch)| ('\x40'..='\x7e').contains(&ch))
+            .map_or(s.len(), |(i, ch)| i + ch.len_utf8()),
+        // OSC: terminated by BEL or ST (ESC \).
+        Some((_, ']')) => {
+            let mut prev = ' ';
+            chars
+                .find(|&(_, ch)| {
+                    let end = ch == BEL || (prev == ESC && ch == '\\');
+                    prev = ch;
+                    end
+                })
+                .map_or(s.len(), |(i, ch)| i + ch.len_utf8())
+        },
+        Some((i, ch)) => i + ch.len_utf8(),
+        None => s.len(),
+    }
+}
+
+/// Removes all escape sequences from `s`.
+pub(crate) fn strip(s: &str) -> Cow<'_, str> {
+    if !s.contains(ESC) {
+        return Cow::Borrowed(s);
+    }
+    Cow::Owned(tokens(s)
+        .filter_map(|token| match token {
+            Token::Char(ch) => Some(ch),
+            Token::Escape(_) => None,
+        })
+        .collect())
+}
+
+/// Makes `s` safe to print on a single row: removes escape sequences
+/// except those setting colors and text attributes (SGR), expands tabs to
+/// the next multiple of `tab_width` columns and replaces other control
+/// characters with `�`.
+pub(crate) fn sanitize(s: &str, tab_width: usize) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut column = 0;
+    for token in tokens(s) {
+        match token {
+            Token::Char('\t') => {
+                let spaces = tab_width - column % tab_width;
+                out.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            },
+            Token::Char(ch) if ch.is_control() => {
+                out.push('\u{fffd}');
+                column += 1;
+            },
+            Token::Char(ch) => {
+                out.push(ch);
+                column += text::char_width(ch);
+            },
+            Token::Escape(escape) if escape.starts_with("\x1b[") && escape.ends_with('m') => out.push_str(escape),
+            Token::Escape(_) => {},
+        }
+    }
+    out
+}
+
+/// Returns the visible columns `skip..skip + take` of `s`, keeping every
+/// escape sequence up to the end of that range so styling is preserved.
+/// Wide characters cut by the range are handled like [`text::slice`].
+pub(crate) fn slice(s: &str, skip: usize, take: usize) -> String {
+    let mut out = String::new();
+    let mut column = 0;
+    for token in tokens(s) {
+        match token {
+            Token::Escape(escape) => out.push_str(escape),
+            Token::Char(ch) => {
+                let start = column;
+                column += text::char_width(ch);
+                if column > skip + take {
+                    break;
+                }
+                if start >= skip {
+                    out.push(ch);
+                } else if column > skip {
+                    out.extend(std::iter::repeat_n(' ', column - skip));
+                }
+            },
+        }
+    }
+    out.push_str(RESET);
+    out
+}
+
+/// Splits `s` into runs of visible text, each with the style its SGR
+/// escape sequences set. Other escape sequences are dropped.
+pub(crate) fn styled(s: &str) -> Vec<(String, ContentStyle)> {
+    let mut runs: Vec<(String, ContentStyle)> = Vec::new();
+    let mut style = ContentStyle::new();
+    for token in tokens(s) {
+        match token {
+            Token::Char(ch) => match runs.last_mut() {
+                Some((text, run_style)) if *run_style == style => text.push(ch),
+                _ => runs.push((ch.to_string(), style)),
+            },
+            Token::Escape(escape) => {
+                if let Some(params) = escape.strip_prefix("\x1b[").and_then(|escape| escape.strip_suffix('m')) {
+                    apply_sgr(&mut style, params);
+                }
+            },
+        }
+    }
+    runs
+}
+
+/// Applies the parameters of an SGR escape sequence to `style`.
+fn apply_sgr(style: &mut ContentStyle, params: &str) {
+    let mut codes = params.split([';', ':']).map(|code| code.parse::<u16>().unwrap_or(0));
+    while let Some(code) = codes.next() {
+        match code {
+            0 => *style = ContentStyle::new(),
+            1 => style.attributes.set(Attribute::Bold),
+            2 => style.attributes.set(Attribute::Dim),
+            3 => style.attributes.set(Attribute::Italic),
+            4 => style.attributes.set(Attribute::Underlined),
+            5 => style.attributes.set(Attribute::SlowBlink),
+            6 => style.attributes.set(Attribute::RapidBlink),
+            7 => style.attributes.set(Attribute::Reverse),
+            8 => style.attributes.set(Attribute::Hidden),
+            9 => style.attributes.set(Attribute::CrossedOut),
+            22 => {
+                style.attributes.unset(Attribute::Bold);
+                style.attributes.unset(Attribute::Dim);
+            },
+            23 => style.attributes.unset(Attribute::Italic),
+            24 => style.attributes.unset(Attribute::Underlined),
+            25 => {
+                style.attributes.unset(Attribute::SlowBlink);
+                style.attributes.unset(Attribute::RapidBlink);
+            },
+            27 => style.attributes.unset(Attribute::Reverse),
+            28 => style.attributes.unset(Attribute::Hidden),
+            29 => style.attributes.unset(Attribute::CrossedOut),
+            30..=37 => style.foreground_color = Some(palette_color(code - 30)),
+            38 => style.foreground_color = extended_color(&mut codes),
+            39 => style.foreground_color = None,
+            40..=47 => style.background_color = Some(palette_color(code - 40)),
+            48 => style.background_color = extended_color(&mut codes),
+            49 => style.background_color = None,
+            90..=97 => style.foreground_color = Some(palette_color(code - 90 + 8)),
+            100..=107 => style.background_color = Some(palette_color(code - 100 + 8)),
+            _ => {},
+        }
+    }
+}
+
+/// Reads the rest of a `38` or `48` SGR parameter: `5;n` for a 256-color
+/// palette entry or `2;r;g;b` for a true color.
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    match codes.next()? {
+        5 => Some(Color::AnsiValue(codes.next()? as u8)),
+        2 => Some(Color::Rgb {
+            r: codes.next()? as u8,
+            g: codes.next()? as u8,
+            b: codes.next()? as u8,
+        }),
+        _ => None,
+    }
+}
+
+/// Returns one of the 16 basic terminal colors, the bright ones from 8.
+fn palette_color(n: u16) -> Color {
+    match n {
+        0 => Color::Black,
+        1 => Color::DarkRed,
+        2 => Color::DarkGreen,
+        3 => Color::DarkYellow,
+        4 => Color::DarkBlue,
+        5 => Color::DarkMagenta,
+        6 => Color::DarkCyan,
+        7 => Color::Grey,
+        8 => Color::DarkGrey,
+        9 => Color::Red,
+        10 => Color::Green,
+        11 => Color::Yellow,
+        12 => Color::Blue,
+        13 => Color::Magenta,
+        14 => Color::Cyan,
+        _ => Color::White,
+    }
+}
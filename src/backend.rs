@@ -0,0 +1,211 @@
+//! Terminal input, modes and drawing, behind a trait so platform
+//! differences stay in one place and the picker can be driven by other
+//! terminal libraries or scripted input in tests.
+
+use std::fmt;
+use std::io::{self, stdout, Write};
+use std::time::Duration;
+use crossterm::{cursor, event, terminal, QueueableCommand};
+use crossterm::event::Event;
+use crossterm::style::{ContentStyle, Print, PrintStyledContent};
+
+/// The terminal operations the picker needs: input, modes and drawing.
+///
+/// Frames are drawn by moving the cursor, clearing lines and printing
+/// styled text, then flushed once. Optional features have defaults, so a
+/// backend only implements what its terminal supports.
+pub trait Backend {
+    /// Returns the terminal's size in columns and rows.
+    fn size(&mut self) -> io::Result<(u16, u16)>;
+
+    /// Switches the terminal to raw mode, delivering key presses
+    /// unbuffered and without echo.
+    fn enable_raw_mode(&mut self) -> io::Result<()>;
+
+    /// Restores the terminal's previous mode.
+    fn disable_raw_mode(&mut self) -> io::Result<()>;
+
+    /// Returns the cursor's column and row, used to render inline.
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)>;
+
+    /// Waits up to `timeout` for an event, returning whether one is ready.
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+
+    /// Reads the next event, blocking until there is one.
+    fn read(&mut self) -> io::Result<Event>;
+
+    /// Switches to the alternate screen, keeping the shell's output.
+    fn enter_alternate_screen(&mut self) -> io::Result<()>;
+
+    /// Switches back to the main screen.
+    fn leave_alternate_screen(&mut self) -> io::Result<()>;
+
+    /// Starts reporting mouse events. Fails by default, in which case the
+    /// picker continues with keyboard input only.
+    fn enable_mouse_capture(&mut self) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// Stops reporting mouse events.
+    fn disable_mouse_capture(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Makes pasted text arrive as a single event. Fails by default, in
+    /// which case pasted text arrives as separate key presses.
+    fn enable_bracketed_paste(&mut self) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// Lets pasted text arrive as key presses again.
+    fn disable_bracketed_paste(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Shows the cursor.
+    fn show_cursor(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Hides the cursor, while a frame is drawn.
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Moves the cursor to `column` and `row`, counted from zero.
+    fn move_cursor(&mut self, column: u16, row: u16) -> io::Result<()>;
+
+    /// Clears the line the cursor is on.
+    fn clear_line(&mut self) -> io::Result<()>;
+
+    /// Prints `text`, which contains no escape sequences, at the cursor.
+    fn print(&mut self, text: &str, style: ContentStyle) -> io::Result<()>;
+
+    /// Makes everything drawn so far visible.
+    fn flush(&mut self) -> io::Result<()>;
+
+    /// Draws on `output` instead of stdout, as chosen with
+    /// [`FuzzyPicker::set_tty_path`](crate::FuzzyPicker::set_tty_path).
+    /// Ignored by default, for backends not drawing on a writer.
+    fn set_output(&mut self, output: Box<dyn Write>) {
+        let _ = output;
+    }
+}
+
+/// The default backend, using crossterm on every platform and drawing
+/// with ANSI escape sequences.
+pub struct CrosstermBackend {
+    output: Box<dyn Write>,
+}
+
+impl CrosstermBackend {
+    /// Constructs a backend drawing on stdout.
+    pub fn new() -> Self {
+        Self { output: Box::new(stdout()) }
+    }
+}
+
+impl Default for CrosstermBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for CrosstermBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CrosstermBackend").finish_non_exhaustive()
+    }
+}
+
+impl Backend for CrosstermBackend {
+    fn size(&mut self) -> io::Result<(u16, u16)> {
+        terminal::size()
+    }
+
+    fn enable_raw_mode(&mut self) -> io::Result<()> {
+        terminal::enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        terminal::disable_raw_mode()
+    }
+
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+        cursor::position()
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        event::read()
+    }
+
+    fn enter_alternate_screen(&mut self) -> io::Result<()> {
+        self.output.queue(terminal::EnterAlternateScreen)?;
+        Ok(())
+    }
+
+    fn leave_alternate_screen(&mut self) -> io::Result<()> {
+        self.output.queue(terminal::LeaveAlternateScreen)?;
+        Ok(())
+    }
+
+    fn enable_mouse_capture(&mut self) -> io::Result<()> {
+        self.output.queue(event::EnableMouseCapture)?;
+        self.output.flush()
+    }
+
+    fn disable_mouse_capture(&mut self) -> io::Result<()> {
+        self.output.queue(event::DisableMouseCapture)?;
+        Ok(())
+    }
+
+    fn enable_bracketed_paste(&mut self) -> io::Result<()> {
+        self.output.queue(event::EnableBracketedPaste)?;
+        self.output.flush()
+    }
+
+    fn disable_bracketed_paste(&mut self) -> io::Result<()> {
+        self.output.queue(event::DisableBracketedPaste)?;
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.output.queue(cursor::Show)?;
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.output.queue(cursor::Hide)?;
+        Ok(())
+    }
+
+    fn move_cursor(&mut self, column: u16, row: u16) -> io::Result<()> {
+        self.output.queue(cursor::MoveTo(column, row))?;
+        Ok(())
+    }
+
+    fn clear_line(&mut self) -> io::Result<()> {
+        self.output.queue(terminal::Clear(terminal::ClearType::CurrentLine))?;
+        Ok(())
+    }
+
+    fn print(&mut self, text: &str, style: ContentStyle) -> io::Result<()> {
+        if style == ContentStyle::new() {
+            self.output.queue(Print(text))?;
+        } else {
+            self.output.queue(PrintStyledContent(style.apply(text)))?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+
+    fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = output;
+    }
+}
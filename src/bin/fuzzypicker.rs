@@ -0,0 +1,107 @@
+//! A minimal fzf workalike: reads items from stdin, lets the user pick one
+//! or more of them on the terminal and prints the selection to stdout.
+//!
+//! Built with the `cli` feature: `cargo install fuzzypicker --features cli`.
+
+use std::env;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use fuzzypicker::{FuzzyPicker, Layout, DEFAULT_TTY_PATH};
+
+const USAGE: &str = "\
+usage: fuzzypicker [options] < items
+
+Reads newline-delimited items from stdin and prints the picked ones.
+
+options:
+  --prompt <label>  Label shown before the query (default \"> \")
+  --height <rows>   Render inline in <rows> lines instead of full screen
+  --reverse         Show the prompt at the bottom
+  -m, --multi       Allow marking several items with Shift-Tab
+  --read0           Read NUL-terminated items instead of lines
+  --print0          Print picked items terminated by NUL instead of newline
+  -h, --help        Print this help
+
+Exits with 0 when items were picked, 130 when cancelled and 2 on errors.";
+
+/// Settings parsed from the command line.
+#[derive(Default)]
+struct Options {
+    prompt: Option<String>,
+    height: Option<u16>,
+    reverse: bool,
+    multi: bool,
+    read0: bool,
+    print0: bool,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--prompt" => options.prompt = Some(args.next().ok_or("--prompt needs a value")?),
+            "--height" => {
+                let rows = args.next().ok_or("--height needs a value")?;
+                options.height = Some(rows.parse().map_err(|_| format!("invalid height `{}`", rows))?);
+            },
+            "--reverse" => options.reverse = true,
+            "-m" | "--multi" => options.multi = true,
+            "--read0" => options.read0 = true,
+            "--print0" => options.print0 = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            },
+            _ => return Err(format!("unknown option `{}`", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("fuzzypicker: {}\n\n{}", error, USAGE);
+            return ExitCode::from(2);
+        },
+    };
+    let mut picker = if options.read0 {
+        let mut picker = FuzzyPicker::new(&[]);
+        picker.read_items_delimited(io::stdin(), b'\0');
+        picker.set_tty_path(DEFAULT_TTY_PATH);
+        picker
+    } else {
+        FuzzyPicker::from_stdin()
+    };
+    if let Some(prompt) = options.prompt {
+        picker.set_prompt_label(prompt);
+    }
+    if let Some(height) = options.height {
+        picker.set_height(height);
+    }
+    if options.reverse {
+        picker.set_layout(Layout::Reverse);
+    }
+    let picked = if options.multi {
+        picker.pick_multi()
+    } else {
+        picker.pick().map(|item| item.into_iter().collect())
+    };
+    match picked {
+        Ok(items) if items.is_empty() => ExitCode::from(130),
+        Ok(items) => {
+            let terminator = if options.print0 { '\0' } else { '\n' };
+            let mut stdout = io::stdout().lock();
+            for item in items {
+                let _ = write!(stdout, "{}{}", item, terminator);
+            }
+            ExitCode::SUCCESS
+        },
+        Err(error) => {
+            eprintln!("fuzzypicker: {}", error);
+            ExitCode::from(2)
+        },
+    }
+}
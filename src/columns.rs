@@ -0,0 +1,301 @@
+//! Splitting display strings into fields and laying them out as columns.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+use std::str::FromStr;
+use crate::text;
+
+/// Horizontal alignment of a column's contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+}
+
+/// A column of a tabular item list, see [`PickerState::set_columns`](crate::PickerState::set_columns).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    header: String,
+    width: Option<usize>,
+    align: Align,
+    searchable: bool,
+    hidden: bool,
+}
+
+impl Column {
+    /// Creates a left-aligned, searchable column as wide as its widest value.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The column's title in the header row. Leave every
+    ///   header empty to hide the header row.
+    pub fn new(header: impl Into<String>) -> Self {
+        Self { header: header.into(), width: None, align: Align::Left, searchable: true, hidden: false }
+    }
+
+    /// Fixes the column's width in characters; longer values are cut off.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Sets the alignment of the column's values.
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Sets whether the query is matched against the column's values.
+    pub fn searchable(mut self, searchable: bool) -> Self {
+        self.searchable = searchable;
+        self
+    }
+
+    /// Hides the column until `Action::ShowColumns` is used. Hidden
+    /// columns are still matched against the query.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    pub(crate) fn header(&self) -> &str {
+        &self.header
+    }
+
+    pub(crate) fn is_searchable(&self) -> bool {
+        self.searchable
+    }
+
+    pub(crate) fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub(crate) fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    pub(crate) fn fixed_width(&self) -> Option<usize> {
+        self.width
+    }
+
+    pub(crate) fn set_width(&mut self, width: usize) {
+        self.width = Some(width);
+    }
+}
+
+/// Gap between adjacent columns.
+const COLUMN_GAP: &str = "  ";
+
+/// Splits `text` into at most `limit` fields separated by `delimiter`, or
+/// by runs of whitespace when there is none. The last field keeps the rest
+/// of the text, delimiters included.
+pub(crate) fn split<'a>(text: &'a str, delimiter: Option<&str>, limit: usize) -> Vec<&'a str> {
+    match delimiter {
+        Some(delimiter) if !delimiter.is_empty() => text.splitn(limit.max(1), delimiter).collect(),
+        _ => {
+            let mut fields = Vec::new();
+            let mut rest = text.trim_start();
+            while !rest.is_empty() && fields.len() + 1 < limit {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                fields.push(&rest[..end]);
+                rest = rest[end..].trim_start();
+            }
+            if !rest.is_empty() {
+                fields.push(rest);
+            }
+            fields
+        },
+    }
+}
+
+/// Widens `widths` to fit `fields`.
+pub(crate) fn measure(widths: &mut Vec<usize>, fields: &[&str]) {
+    if widths.len() < fields.len() {
+        widths.resize(fields.len(), 0);
+    }
+    for (width, field) in widths.iter_mut().zip(fields) {
+        *width = (*width).max(text::width(field));
+    }
+}
+
+/// Lays out `fields` as a row of the visible `columns`, using `widths` for
+/// columns without a fixed width. The last column is left unpadded.
+pub(crate) fn format_row(columns: &[Column], widths: &[usize], fields: &[&str]) -> String {
+    let mut row = String::new();
+    let first_visible = columns.iter().position(|column| !column.hidden);
+    let last_visible = columns.iter().rposition(|column| !column.hidden);
+    for (i, column) in columns.iter().enumerate() {
+        if column.hidden {
+            continue;
+        }
+        let field = fields.get(i).copied().unwrap_or_default();
+        let last = Some(i) == last_visible;
+        if Some(i) != first_visible {
+            row.push_str(COLUMN_GAP);
+        }
+        let width = match column.width {
+            Some(width) => width,
+            None if last => {
+                row.push_str(field);
+                break;
+            },
+            None => widths.get(i).copied().unwrap_or_default(),
+        };
+        let value = text::slice(field, 0, width);
+        let padding = " ".repeat(width - text::width(&value));
+        match column.align {
+            Align::Left if last => row.push_str(&value),
+            Align::Left => {
+                row.push_str(&value);
+                row.push_str(&padding);
+            },
+            Align::Right => {
+                row.push_str(&padding);
+                row.push_str(&value);
+            },
+        }
+    }
+    row
+}
+
+/// Returns the start and width of the visible column at `index` in rows
+/// laid out by [`format_row`].
+pub(crate) fn span(columns: &[Column], widths: &[usize], index: usize) -> Option<(usize, usize)> {
+    let width = |(i, column): (usize, &Column)| column.width.unwrap_or_else(|| widths.get(i).copied().unwrap_or_default());
+    if columns.get(index)?.hidden {
+        return None;
+    }
+    let start = columns.iter()
+        .enumerate()
+        .take(index)
+        .filter(|(_, column)| !column.hidden)
+        .map(|column| width(column) + COLUMN_GAP.len())
+        .sum();
+    Some((start, width((index, &columns[index]))))
+}
+
+/// Column widths and visibility persisted to a file, one column per line:
+/// its header, its width (empty while fitted to its values) and `hidden`
+/// or `shown`, separated by tabs.
+pub(crate) struct ColumnStore {
+    path: PathBuf,
+}
+
+impl ColumnStore {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Applies the saved widths and visibility to the columns with the
+    /// same headers. A missing file changes nothing.
+    pub(crate) fn load(&self, columns: &mut [Column]) -> io::Result<()> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        for line in contents.lines() {
+            let mut fields = line.split('\t');
+            let (Some(header), Some(width), Some(hidden)) = (fields.next(), fields.next(), fields.next()) else {
+                continue;
+            };
+            for column in columns.iter_mut().filter(|column| column.header == header) {
+                column.width = width.parse().ok();
+                column.hidden = hidden == "hidden";
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the widths and visibility of `columns` to the file.
+    pub(crate) fn save(&self, columns: &[Column]) -> io::Result<()> {
+        let contents: String = columns.iter()
+            .map(|column| format!(
+                "{}\t{}\t{}\n",
+                column.header,
+                column.width.map(|width| width.to_string()).unwrap_or_default(),
+                if column.hidden { "hidden" } else { "shown" },
+            ))
+            .collect();
+        fs::write(&self.path, contents)
+    }
+}
+
+/// Fields of an item that take part in matching, like fzf's `--nth`.
+///
+/// Parsed from a comma-separated list of 1-based field indexes and ranges:
+/// `2`, `-1` (the last field), `3..`, `..2`, `1..-2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpec {
+    ranges: Vec<(Option<isize>, Option<isize>)>,
+}
+
+/// The error returned when parsing a [`FieldSpec`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFieldSpecError {
+    part: String,
+}
+
+impl fmt::Display for ParseFieldSpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid field range `{}`", self.part)
+    }
+}
+
+impl Error for ParseFieldSpecError {}
+
+impl FromStr for FieldSpec {
+    type Err = ParseFieldSpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let index = |part: &str, bound: &str| -> Result<Option<isize>, ParseFieldSpecError> {
+            if bound.is_empty() {
+                return Ok(None);
+            }
+            match bound.parse::<isize>() {
+                Ok(0) | Err(_) => Err(ParseFieldSpecError { part: part.to_string() }),
+                Ok(index) => Ok(Some(index)),
+            }
+        };
+        let ranges = s.split(',')
+            .map(str::trim)
+            .map(|part| match part.split_once("..") {
+                Some((start, end)) => Ok((index(part, start)?, index(part, end)?)),
+                None if part.is_empty() => Err(ParseFieldSpecError { part: part.to_string() }),
+                None => {
+                    let index = index(part, part)?;
+                    Ok((index, index))
+                },
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { ranges })
+    }
+}
+
+impl FieldSpec {
+    /// Returns the fields of `text` selected by the spec, rejoined with
+    /// the delimiter.
+    pub(crate) fn select(&self, text: &str, delimiter: Option<&str>) -> String {
+        let fields = split(text, delimiter, usize::MAX);
+        let count = fields.len() as isize;
+        // Converts a 1-based or negative index to a 0-based one.
+        let resolve = |index: isize| if index > 0 { index - 1 } else { count + index };
+        let mut selected = Vec::new();
+        for &(start, end) in &self.ranges {
+            let start = start.map_or(0, resolve).max(0);
+            let end = end.map_or(count - 1, resolve).min(count - 1);
+            if start <= end {
+                selected.extend_from_slice(&fields[start as usize..=end as usize]);
+            }
+        }
+        let separator = match delimiter {
+            Some(delimiter) if !delimiter.is_empty() => delimiter,
+            _ => " ",
+        };
+        selected.join(separator)
+    }
+}
@@ -0,0 +1,49 @@
+//! The original `new()`/`set_items()`/`pick()` API, kept working on top of
+//! the current picker so existing tools can upgrade one call at a time.
+//!
+//! Everything here is deprecated. Use [`crate::FuzzyPicker`] instead, and
+//! [`pick_outcome()`](crate::FuzzyPicker::pick_outcome) to learn how
+//! picking ended rather than only whether an item was picked.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #![allow(deprecated)]
+//! use fuzzypicker::compat::FuzzyPicker;
+//!
+//! let mut picker = FuzzyPicker::new(&["rust", "python"]);
+//! if let Ok(Some(language)) = picker.pick() {
+//!     println!("{}", language);
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt::Display;
+
+/// A picker with the original API.
+#[deprecated(note = "use `fuzzypicker::FuzzyPicker`, whose `pick_outcome()` reports how picking ended")]
+pub struct FuzzyPicker<T: Display + Clone> {
+    picker: crate::FuzzyPicker<T>,
+}
+
+#[allow(deprecated)]
+impl<T: Display + Clone> FuzzyPicker<T> {
+    /// Constructs a picker for `items`.
+    #[deprecated(note = "use `fuzzypicker::FuzzyPicker::new`")]
+    pub fn new(items: &[T]) -> Self {
+        Self { picker: crate::FuzzyPicker::new(items) }
+    }
+
+    /// Replaces the items to pick from.
+    #[deprecated(note = "use `fuzzypicker::FuzzyPicker::set_items_iter`")]
+    pub fn set_items(&mut self, items: &[T]) {
+        self.picker.set_items_iter(items.iter().cloned());
+    }
+
+    /// Lets the user pick an item, returning `Ok(None)` if the selection
+    /// is cancelled.
+    #[deprecated(note = "use `fuzzypicker::FuzzyPicker::pick_outcome`, which also reports the accepting key, the query and time-outs")]
+    pub fn pick(&mut self) -> Result<Option<T>, Box<dyn Error>> {
+        Ok(self.picker.pick_outcome()?.into_item())
+    }
+}
@@ -0,0 +1,255 @@
+//! Loading picker settings from a configuration file.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::time::Duration;
+use serde::Deserialize;
+use crate::{Action, FuzzyPicker, HighlightMode, Key, Layout, MatchMode, MouseMode, PickerState, PreviewPosition, RankingMode};
+
+/// Picker settings that end users can customize, e.g. in a TOML or JSON
+/// configuration file of an application embedding the picker.
+///
+/// Every setting is optional and left at the application's choice when
+/// missing. Apply a configuration with [`FuzzyPicker::with_config`], or
+/// [`PickerState::with_config`] when embedding the picker, which ignores
+/// the settings of the terminal.
+///
+/// # Example
+///
+/// A configuration in TOML:
+///
+/// ```toml
+/// layout = "reverse"
+/// height = 15
+/// prompt_label = "❯ "
+/// line_wrap = true
+/// match_mode = "exact"
+/// unbind = ["ctrl-q"]
+///
+/// [keybindings]
+/// ctrl-j = "select-next"
+/// ctrl-k = "select-prev"
+/// alt-1 = { quick-select = 1 }
+///
+/// [columns]
+/// NAME = { width = 20 }
+/// PID = { hidden = true }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PickerConfig {
+    /// Replaces the `"> "` label shown before the query.
+    pub prompt_label: Option<String>,
+    /// Places the prompt at the top or bottom: `"default"` or `"reverse"`.
+    pub layout: Option<Layout>,
+    /// Renders the picker inline, in this many rows below the cursor.
+    pub height: Option<u16>,
+    /// How matches are ordered, e.g. `"score"` or `"preserve-order"`.
+    pub ranking_mode: Option<RankingMode>,
+    /// How the query is matched, e.g. `"fuzzy"` or `"exact"`.
+    pub match_mode: Option<MatchMode>,
+    /// Whether item display strings may contain ANSI color codes.
+    pub ansi: Option<bool>,
+    /// Whether long items wrap onto several rows.
+    pub line_wrap: Option<bool>,
+    /// Whether moving past either end of the list wraps around.
+    pub wrap: Option<bool>,
+    /// Replaces the pointer shown before the highlighted item.
+    pub pointer: Option<String>,
+    /// Replaces the marker shown before marked items.
+    pub marker: Option<String>,
+    /// Highlights the whole row, or only the pointer: `"row"` or `"pointer"`.
+    pub highlight_mode: Option<HighlightMode>,
+    /// Whether the first ten visible items are numbered for `Alt-1` … `Alt-0`.
+    pub quick_select: Option<bool>,
+    /// Whether a single remaining match is accepted without `Enter`.
+    pub auto_accept_single: Option<bool>,
+    /// Whether picking ends right away when there are no items.
+    pub exit_on_empty: Option<bool>,
+    /// Whether `Enter` accepts the query while nothing matches.
+    pub accept_query: Option<bool>,
+    /// Whether the accessible mode is on, see
+    /// [`PickerState::set_accessible`].
+    pub accessible: Option<bool>,
+    /// Places the preview pane: `"right"` or `"bottom"`.
+    pub preview_position: Option<PreviewPosition>,
+    /// Percentage of the width or height taken by the preview pane.
+    pub preview_size: Option<u16>,
+    /// Whether the mouse is captured, overriding the detection of
+    /// terminals supporting it, see [`FuzzyPicker::set_mouse_mode`].
+    /// Only applies to a [`FuzzyPicker`], since an embedding application
+    /// owns the terminal.
+    pub mouse: Option<bool>,
+    /// Lines scrolled by one mouse wheel step.
+    pub scroll_step: Option<usize>,
+    /// Rows kept visible above and below the highlighted item.
+    pub scrolloff: Option<usize>,
+    /// Maximum delay between the clicks of a double-click, in milliseconds.
+    pub double_click_interval_ms: Option<u64>,
+    /// Delay before matching after the query was typed into, in milliseconds.
+    pub debounce_ms: Option<u64>,
+    /// How often the picker wakes up without input, in milliseconds.
+    /// Only applies to a [`FuzzyPicker`], since an embedding application
+    /// runs its own event loop.
+    pub tick_rate_ms: Option<u64>,
+    /// File remembering accepted queries across invocations.
+    pub history_file: Option<PathBuf>,
+    /// Maximum number of remembered queries.
+    pub history_size: Option<usize>,
+    /// Message shown when there are no items.
+    pub empty_message: Option<String>,
+    /// Message shown when the query matches nothing.
+    pub no_match_message: Option<String>,
+    /// Keys bound to actions, e.g. `"ctrl-j" = "select-next"`. Replaces
+    /// existing bindings of the same keys.
+    pub keybindings: HashMap<Key, Action>,
+    /// Keys whose bindings are removed, e.g. `["ctrl-q"]`.
+    pub unbind: Vec<Key>,
+    /// Default widths and visibility of the columns with these headers,
+    /// e.g. `NAME = { width = 20 }`. A layout saved with
+    /// [`PickerState::set_column_layout_file`] takes precedence.
+    pub columns: HashMap<String, ColumnConfig>,
+}
+
+/// Settings of a column in a [`PickerConfig`], see
+/// [`PickerState::set_columns`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColumnConfig {
+    /// Fixes the column's width in characters.
+    pub width: Option<usize>,
+    /// Whether the column is hidden until `Action::ShowColumns` is used.
+    pub hidden: Option<bool>,
+}
+
+impl<T: Display + Clone> PickerState<T> {
+    /// Applies the settings present in `config`, see [`PickerConfig`].
+    ///
+    /// `mouse` and `tick_rate_ms` are ignored, as they configure the
+    /// terminal and event loop, which the embedding application owns.
+    pub fn with_config(mut self, config: PickerConfig) -> Self {
+        self.apply_config(config);
+        self
+    }
+
+    fn apply_config(&mut self, config: PickerConfig) {
+        if let Some(label) = config.prompt_label {
+            self.set_prompt_label(label);
+        }
+        if let Some(layout) = config.layout {
+            self.set_layout(layout);
+        }
+        if let Some(rows) = config.height {
+            self.set_height(rows);
+        }
+        if let Some(mode) = config.ranking_mode {
+            self.set_ranking_mode(mode);
+        }
+        if let Some(mode) = config.match_mode {
+            self.set_match_mode(mode);
+        }
+        if let Some(enabled) = config.ansi {
+            self.set_ansi(enabled);
+        }
+        if let Some(enabled) = config.line_wrap {
+            self.set_line_wrap(enabled);
+        }
+        if let Some(enabled) = config.wrap {
+            self.set_wrap(enabled);
+        }
+        if let Some(pointer) = config.pointer {
+            self.set_pointer(pointer);
+        }
+        if let Some(marker) = config.marker {
+            self.set_marker(marker);
+        }
+        if let Some(mode) = config.highlight_mode {
+            self.set_highlight_mode(mode);
+        }
+        if let Some(enabled) = config.quick_select {
+            self.set_quick_select(enabled);
+        }
+        if let Some(enabled) = config.auto_accept_single {
+            self.set_auto_accept_single(enabled);
+        }
+        if let Some(enabled) = config.exit_on_empty {
+            self.set_exit_on_empty(enabled);
+        }
+        if let Some(enabled) = config.accept_query {
+            self.set_accept_query(enabled);
+        }
+        if let Some(enabled) = config.accessible {
+            self.set_accessible(enabled);
+        }
+        if config.preview_position.is_some() || config.preview_size.is_some() {
+            self.set_preview_window(
+                config.preview_position.unwrap_or(self.preview_position),
+                config.preview_size.unwrap_or(self.preview_size),
+            );
+        }
+        if let Some(lines) = config.scroll_step {
+            self.set_scroll_step(lines);
+        }
+        if let Some(rows) = config.scrolloff {
+            self.set_scrolloff(rows);
+        }
+        if let Some(ms) = config.double_click_interval_ms {
+            self.set_double_click_interval(Duration::from_millis(ms));
+        }
+        if let Some(ms) = config.debounce_ms {
+            self.set_debounce(Duration::from_millis(ms));
+        }
+        if let Some(path) = config.history_file {
+            self.set_history_file(path);
+        }
+        if let Some(size) = config.history_size {
+            self.set_history_size(size);
+        }
+        if let Some(message) = config.empty_message {
+            self.set_empty_message(message);
+        }
+        if let Some(message) = config.no_match_message {
+            self.set_no_match_message(message);
+        }
+        for key in &config.unbind {
+            self.unbind(*key);
+        }
+        for (key, action) in config.keybindings {
+            self.bind(key, action);
+        }
+        for (header, column) in config.columns {
+            self.column_defaults.insert(header, (column.width, column.hidden));
+        }
+        self.apply_column_defaults();
+    }
+}
+
+impl<T: Display + Clone> FuzzyPicker<T> {
+    /// Applies the settings present in `config`, e.g. one deserialized from
+    /// a user's configuration file, on top of those set so far.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::{FuzzyPicker, Layout, PickerConfig};
+    ///
+    /// let config = PickerConfig {
+    ///     layout: Some(Layout::Reverse),
+    ///     prompt_label: Some(String::from("❯ ")),
+    ///     ..PickerConfig::default()
+    /// };
+    /// let mut picker = FuzzyPicker::new(&["apple", "banana"]).with_config(config);
+    /// let picked = picker.pick();
+    /// ```
+    pub fn with_config(mut self, config: PickerConfig) -> Self {
+        if let Some(enabled) = config.mouse {
+            self.set_mouse_mode(if enabled { MouseMode::Always } else { MouseMode::Never });
+        }
+        if let Some(ms) = config.tick_rate_ms {
+            self.set_tick_rate(Duration::from_millis(ms));
+        }
+        self.apply_config(config);
+        self
+    }
+}
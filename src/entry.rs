@@ -0,0 +1,59 @@
+//! Items shown by a label and picked for a key.
+
+use std::fmt;
+
+/// An item shown as its `label` that carries a `key`, e.g. a database id,
+/// so pickers can show one thing and return another without a newtype
+/// implementing `Display`.
+///
+/// # Example
+///
+/// ```no_run
+/// use fuzzypicker::{Entry, FuzzyPicker};
+///
+/// let users = [(17, "alice"), (42, "bob")];
+/// let entries: Vec<Entry<u32, &str>> = users.iter()
+///     .map(|&(id, name)| Entry::new(id, name))
+///     .collect();
+/// let mut picker = FuzzyPicker::new(&entries);
+/// if let Ok(Some(entry)) = picker.pick() {
+///     println!("user id {}", entry.into_key());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry<K, V> {
+    /// The value returned when the entry is picked.
+    pub key: K,
+    /// The text shown for the entry and matched against the query.
+    pub label: V,
+}
+
+impl<K, V> Entry<K, V> {
+    /// Creates an entry shown as `label`.
+    pub fn new(key: K, label: V) -> Self {
+        Self { key, label }
+    }
+
+    /// Creates an entry whose label is derived from `key`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The value returned when the entry is picked.
+    /// * `label` - Produces the text shown for the entry.
+    pub fn with_label(key: K, label: impl FnOnce(&K) -> V) -> Self {
+        let label = label(&key);
+        Self { key, label }
+    }
+
+    /// Returns the entry's key, dropping its label.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+}
+
+impl<K, V: fmt::Display> fmt::Display for Entry<K, V> {
+    /// Writes the entry's label.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.label.fmt(f)
+    }
+}
@@ -0,0 +1,56 @@
+//! The error type of the one-shot [`pick`](crate::pick) function.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// An error that ended an interactive selection.
+#[derive(Debug)]
+pub enum PickError {
+    /// The terminal could not be set up, drawn to or read from.
+    Io(io::Error),
+    /// Stdout is not a terminal and drawing elsewhere was not allowed,
+    /// see [`TtyFallback`](crate::TtyFallback).
+    NotATty,
+    /// Any other failure.
+    Other(Box<dyn Error>),
+}
+
+impl fmt::Display for PickError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PickError::Io(error) => write!(f, "terminal error: {}", error),
+            PickError::NotATty => f.write_str("stdout is not a terminal"),
+            PickError::Other(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for PickError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PickError::Io(error) => Some(error),
+            PickError::NotATty => None,
+            PickError::Other(error) => error.source(),
+        }
+    }
+}
+
+impl From<io::Error> for PickError {
+    fn from(error: io::Error) -> Self {
+        PickError::Io(error)
+    }
+}
+
+impl From<Box<dyn Error>> for PickError {
+    fn from(error: Box<dyn Error>) -> Self {
+        let error = match error.downcast::<PickError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        match error.downcast::<io::Error>() {
+            Ok(error) => PickError::Io(*error),
+            Err(error) => PickError::Other(error),
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! The contents of the picker's screen, laid out once for every frontend:
+//! the terminal and the ratatui widget only draw the spans of a [`Frame`].
+
+use std::fmt::Display;
+use std::iter;
+use std::ops::Range;
+use crossterm::style::{ContentStyle, Stylize};
+use crate::theme::Theme;
+use crate::{ansi, columns, text, Column, FooterInfo, Layout, PickerState, HELP_TITLE};
+
+/// Text drawn in a single style from a column of a row.
+pub(crate) struct Span {
+    /// Column relative to the picker's left edge.
+    pub(crate) column: usize,
+    /// Row of the screen, or of the area the picker is drawn into.
+    pub(crate) row: u16,
+    /// The text, without escape sequences.
+    pub(crate) text: String,
+    pub(crate) style: ContentStyle,
+}
+
+/// Everything drawn for the picker's current state.
+pub(crate) struct Frame {
+    /// The rows taken by the picker, blank apart from the spans.
+    pub(crate) rows: Range<u16>,
+    pub(crate) spans: Vec<Span>,
+    /// Column and row of the text cursor, after the query.
+    pub(crate) cursor: (usize, u16),
+}
+
+/// A prefix drawn before an item: its quick-select number and decoration.
+type Prefix = Vec<(String, ContentStyle)>;
+
+impl Frame {
+    fn push(&mut self, column: usize, row: u16, text: impl Into<String>, style: ContentStyle) {
+        let text = text.into();
+        if !text.is_empty() {
+            self.spans.push(Span { column, row, text, style });
+        }
+    }
+
+    /// Adds runs of text in their own styles, one after another.
+    fn push_runs(&mut self, mut column: usize, row: u16, runs: impl IntoIterator<Item = (String, ContentStyle)>) {
+        for (text, style) in runs {
+            let width = text::width(&text);
+            self.push(column, row, text, style);
+            column += width;
+        }
+    }
+
+    /// Adds text styled by the SGR escape sequences it contains.
+    fn push_ansi(&mut self, column: usize, row: u16, text: &str) {
+        self.push_runs(column, row, ansi::styled(text));
+    }
+}
+
+impl<T: Display + Clone> PickerState<T> {
+    /// Lays out the picker for drawing, using colors if `colors` is set.
+    pub(crate) fn frame(&mut self, colors: bool) -> Frame {
+        self.fit_wrapped_viewport();
+        self.drawn_spinner = self.spinner();
+        let theme = Theme::new(colors, self.accessible, &self.indicators);
+        let prompt_row = self.prompt_row();
+        let mut frame = Frame {
+            rows: self.top..self.top + self.height as u16,
+            spans: Vec::new(),
+            cursor: (0, prompt_row),
+        };
+        let (prompt, prompt_style) = match &self.confirming {
+            Some((item, _)) => (format!("Accept '{}'? (y/n)", self.plain_text(&item.to_string())), theme.question),
+            None => (format!("{}{}", self.prompt_prefix(), self.prompt), theme.prompt),
+        };
+        let prompt = text::slice(&prompt, 0, self.width);
+        let prompt_width = text::width(&prompt);
+        frame.cursor.0 = prompt_width.min(self.width.saturating_sub(1));
+        frame.push(0, prompt_row, prompt, prompt_style);
+        if self.compact() {
+            // The highlighted item follows the prompt on the same line.
+            let column = prompt_width + 2;
+            if let (None, Some(item)) = (&self.confirming, self.compact_item()) {
+                let item = text::slice(&item, 0, self.width.saturating_sub(column));
+                frame.push(column, prompt_row, item, theme.selected);
+            }
+            return frame;
+        }
+        if !self.debug.is_empty() {
+            frame.push(20, prompt_row, self.debug.clone(), theme.error);
+        }
+        let (info, info_style) = match &self.query_error {
+            Some(error) => (error.clone(), theme.error),
+            None if self.selection_limit_hit => (self.prompt_info(), theme.error),
+            None => (self.prompt_info(), theme.info),
+        };
+        let column = self.width.saturating_sub(text::width(&info));
+        frame.push(column, prompt_row, info, info_style);
+
+        let text_width = self.list_width().saturating_sub(2);
+        let prefixes = self.prefixes(&theme, colors);
+        let prefix_width = prefixes.iter()
+            .map(|prefix| prefix.iter().map(|(text, _)| text::width(text)).sum::<usize>())
+            .max()
+            .unwrap_or_default();
+        // Prefixes are followed by a space, and items are indented to match.
+        let indent = if prefix_width > 0 { prefix_width + 1 } else { 0 };
+        self.decoration_indent = indent;
+        let item_width = text_width.saturating_sub(indent);
+        for (line, text) in self.header.iter().enumerate() {
+            frame.push(2, self.header_row(line), text::slice(text, 0, text_width), ContentStyle::new());
+        }
+        if self.has_column_header() {
+            let titles: Vec<&str> = self.columns.iter().map(Column::header).collect();
+            let titles = columns::format_row(&self.columns, &self.column_widths, &titles);
+            let row = self.header_row(self.header.len());
+            frame.push(2 + indent, row, text::slice(&titles, self.scroll_offset, item_width), theme.header);
+            if let Some((start, width)) = self.focused_column_span() {
+                // Underline the title of the column the column actions apply to.
+                let end = (start + width).min(self.scroll_offset + item_width);
+                let start = start.max(self.scroll_offset);
+                if start < end {
+                    let title = text::slice(&titles, start, end - start);
+                    frame.push(2 + indent + start - self.scroll_offset, row, title, theme.header.underlined());
+                }
+            }
+        }
+        if let Some(footer) = &self.footer {
+            let info = FooterInfo {
+                query: &self.prompt,
+                matched: self.num_of_matches,
+                total: self.items.len(),
+                selected: self.selected_match().map(|matched| &self.items[matched.index]),
+            };
+            frame.push(0, self.footer_row(), text::slice(&footer(&info), 0, self.width), theme.dim);
+        }
+
+        if self.help_visible {
+            let lines = iter::once(String::from(HELP_TITLE)).chain(self.help_lines());
+            for (offset, line) in lines.take(self.list_height()).enumerate() {
+                let style = if offset == 0 { theme.header } else { ContentStyle::new() };
+                frame.push(2, self.item_row(offset), text::slice(&line, 0, self.width.saturating_sub(2)), style);
+            }
+            return frame;
+        }
+
+        // Until the first match finishes, no matches does not mean nothing matches.
+        if self.matches.is_empty() && self.match_job.is_none() && self.list_height() > 0 {
+            let message = if !self.items.is_empty() {
+                self.no_match_message.as_str()
+            } else if self.item_source.is_some() {
+                "loading…"
+            } else {
+                self.empty_message.as_str()
+            };
+            frame.push(2, self.item_row(0), text::slice(message, 0, text_width), theme.dim);
+        }
+        self.push_items(&mut frame, &theme, colors, prefixes, indent, item_width);
+
+        if let Some((row, tooltip)) = self.tooltip_line() {
+            let tooltip = text::slice(&tooltip, 0, text_width.saturating_sub(2));
+            frame.push(2, row, format!(" {} ", tooltip), theme.tooltip);
+        }
+        for (column, row, text) in self.preview_frame(colors) {
+            frame.push_ansi(column, row, &text);
+        }
+        frame
+    }
+
+    /// Returns the prefix of each visible row: the quick-select number and
+    /// the decoration of items, nothing for group headers.
+    fn prefixes(&self, theme: &Theme, colors: bool) -> Vec<Prefix> {
+        let quick_select_positions = self.quick_select_positions();
+        self.matches.iter()
+            .enumerate()
+            .skip(self.start_index)
+            .take(self.num_of_displayable_items)
+            .map(|(position, matched)| {
+                let mut prefix = Vec::new();
+                match quick_select_positions.iter().position(|&p| p == position) {
+                    Some(n) => prefix.push((((n + 1) % 10).to_string(), theme.dim)),
+                    None if self.quick_select => prefix.push((String::from(" "), ContentStyle::new())),
+                    None => {},
+                }
+                match &self.decoration {
+                    Some(decoration) if matched.header.is_none() => {
+                        if self.quick_select {
+                            prefix.push((String::from(" "), ContentStyle::new()));
+                        }
+                        let decoration = decoration(&self.items[matched.index]);
+                        if colors {
+                            prefix.extend(ansi::styled(&decoration));
+                        } else {
+                            prefix.push((ansi::strip(&decoration).into_owned(), ContentStyle::new()));
+                        }
+                    },
+                    _ => {},
+                }
+                prefix
+            })
+            .collect()
+    }
+
+    /// Adds the visible group headers and items, with the pointer, marker
+    /// and prefix before each item.
+    fn push_items(&self, frame: &mut Frame, theme: &Theme, colors: bool, prefixes: Vec<Prefix>, indent: usize, item_width: usize) {
+        let visible_items = self.matches.iter().enumerate()
+            .skip(self.start_index)
+            .take(self.num_of_displayable_items);
+        let list_height = self.list_height();
+        let mut first_line = 0;
+        for ((index, matched), prefix) in visible_items.zip(prefixes) {
+            if let Some(header) = &matched.header {
+                if first_line < list_height {
+                    let header = text::slice(header, 0, self.list_width());
+                    frame.push(0, self.item_row(first_line), header, theme.header);
+                }
+                first_line += 1;
+                continue;
+            }
+            let item = &self.display_text(self.item_display(matched.index));
+            let lines = if self.line_wrap {
+                text::width(&self.plain_text(item)).div_ceil(item_width.max(1)).max(1)
+            } else {
+                1
+            };
+            let selected = index == self.selected;
+            let mut prefix = Some(prefix);
+            for line in 0..lines {
+                // Wrapped items read top to bottom in both layouts.
+                let offset = match self.layout {
+                    Layout::Default => first_line + line,
+                    Layout::Reverse => first_line + lines - 1 - line,
+                };
+                if offset >= list_height {
+                    continue;
+                }
+                let skip = if self.line_wrap { line * item_width } else { self.scroll_offset };
+                let row = self.item_row(offset);
+                let pointer = if selected && line == 0 { theme.pointer.as_str() } else { " " };
+                frame.push(0, row, pointer, theme.gutter);
+                let marker = if line == 0 && self.marked.contains(&matched.index) { theme.mark.as_str() } else { " " };
+                frame.push(1, row, marker, if selected { theme.selected_marker } else { theme.marker });
+                if line == 0 {
+                    if let Some(prefix) = prefix.take() {
+                        frame.push_runs(2, row, prefix);
+                    }
+                }
+                let column = 2 + indent;
+                if selected {
+                    frame.push(column, row, text::slice(&self.plain_text(item), skip, item_width), theme.selected);
+                } else if self.is_disabled(matched.index) {
+                    frame.push(column, row, text::slice(&self.plain_text(item), skip, item_width), theme.disabled);
+                } else if self.ansi && colors {
+                    frame.push_ansi(column, row, &ansi::slice(item, skip, item_width));
+                } else {
+                    frame.push(column, row, text::slice(&self.plain_text(item), skip, item_width), ContentStyle::new());
+                }
+            }
+            first_line += lines;
+        }
+    }
+}
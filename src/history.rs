@@ -0,0 +1,80 @@
+//! Query history persisted to a file, one query per line.
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+
+/// Default number of queries kept in the history file.
+pub(crate) const DEFAULT_HISTORY_SIZE: usize = 1000;
+
+pub(crate) struct History {
+    path: PathBuf,
+    size: usize,
+    entries: Vec<String>,
+    /// Index of the entry being shown, or `None` while editing a new query.
+    position: Option<usize>,
+    /// The query being edited before browsing the history started.
+    draft: String,
+}
+
+impl History {
+    pub(crate) fn new(path: PathBuf, size: usize) -> Self {
+        Self { path, size, entries: Vec::new(), position: None, draft: String::new() }
+    }
+
+    pub(crate) fn set_size(&mut self, size: usize) {
+        self.size = size;
+    }
+
+    /// Reads the entries from the history file. A missing file is an empty history.
+    pub(crate) fn load(&mut self) -> io::Result<()> {
+        self.position = None;
+        self.entries = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents.lines().map(String::from).collect(),
+            Err(error) if error.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error),
+        };
+        Ok(())
+    }
+
+    /// Steps back to an older query, returning it.
+    pub(crate) fn prev(&mut self, current: &str) -> Option<&str> {
+        let position = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            },
+            Some(position) => position.saturating_sub(1),
+        };
+        self.position = Some(position);
+        Some(&self.entries[position])
+    }
+
+    /// Steps forward to a newer query, returning it. Stepping past the
+    /// newest entry returns the query that was being edited.
+    pub(crate) fn next(&mut self) -> Option<&str> {
+        let position = self.position?;
+        if position + 1 < self.entries.len() {
+            self.position = Some(position + 1);
+            Some(&self.entries[position + 1])
+        } else {
+            self.position = None;
+            Some(&self.draft)
+        }
+    }
+
+    /// Appends `query` to the history file, keeping at most `size` entries.
+    pub(crate) fn record(&mut self, query: &str) -> io::Result<()> {
+        self.position = None;
+        if query.is_empty() || self.entries.last().is_some_and(|last| last == query) {
+            return Ok(());
+        }
+        self.entries.push(query.to_string());
+        let excess = self.entries.len().saturating_sub(self.size);
+        self.entries.drain(..excess);
+        let mut contents = self.entries.join("\n");
+        contents.push('\n');
+        fs::write(&self.path, contents)
+    }
+}
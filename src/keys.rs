@@ -0,0 +1,411 @@
+//! Key bindings for the picker.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A key press: a key code together with its modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Constructs a key from a key code and modifiers.
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Constructs a key without modifiers.
+    pub const fn plain(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    /// Constructs a `Ctrl` + character key.
+    pub const fn ctrl(ch: char) -> Self {
+        Self::new(KeyCode::Char(ch), KeyModifiers::CONTROL)
+    }
+
+    /// Constructs an `Alt` + character key.
+    pub const fn alt(ch: char) -> Self {
+        Self::new(KeyCode::Char(ch), KeyModifiers::ALT)
+    }
+
+    /// Returns the character typed with this key, unless it is a control
+    /// character or `Ctrl` or `Alt` make the key a shortcut. On Windows,
+    /// which reports `AltGr` as `Ctrl` + `Alt`, such keys still type
+    /// characters; elsewhere they are shortcuts like any other.
+    pub(crate) fn typed_char(&self) -> Option<char> {
+        let KeyCode::Char(ch) = self.code else { return None; };
+        let alt_gr = cfg!(windows) && self.modifiers == KeyModifiers::CONTROL | KeyModifiers::ALT;
+        if ch.is_control() || !(self.modifiers.is_empty() || alt_gr) {
+            return None;
+        }
+        Some(ch)
+    }
+}
+
+impl From<KeyCode> for Key {
+    fn from(code: KeyCode) -> Self {
+        Self::plain(code)
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers;
+        if let KeyCode::Char(_) | KeyCode::BackTab = event.code {
+            // Shift is already reflected in the character or key itself.
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self::new(event.code, modifiers)
+    }
+}
+
+/// The error returned when parsing a [`Key`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError {
+    key: String,
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid key `{}`", self.key)
+    }
+}
+
+impl Error for ParseKeyError {}
+
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    /// Parses a key written like fzf's `--bind` keys: a key name or single
+    /// character, optionally prefixed with `ctrl-`, `alt-` and `shift-`,
+    /// e.g. `ctrl-d`, `alt-enter`, `shift-up` or `f5`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseKeyError { key: s.to_string() };
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        while let Some((prefix, name)) = rest.split_once('-').filter(|(_, name)| !name.is_empty()) {
+            modifiers |= match prefix.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => break,
+            };
+            rest = name;
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(ch), None) => KeyCode::Char(ch),
+            _ => match rest.to_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "btab" | "backtab" => KeyCode::BackTab,
+                "bspace" | "backspace" => KeyCode::Backspace,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pgup" | "page-up" => KeyCode::PageUp,
+                "pgdn" | "page-down" => KeyCode::PageDown,
+                "del" | "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=24) => KeyCode::F(n),
+                    _ => return Err(error()),
+                },
+            },
+        };
+        let key = match code {
+            // Shift is reflected in the character or key itself, as for key events.
+            KeyCode::Char(ch) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                Key::new(KeyCode::Char(ch.to_ascii_uppercase()), modifiers)
+            },
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                Key::new(KeyCode::BackTab, modifiers)
+            },
+            code => Key::new(code, modifiers),
+        };
+        Ok(key)
+    }
+}
+
+impl fmt::Display for Key {
+    /// Writes the key in the format accepted by its `FromStr` impl.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, prefix) in [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(prefix)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(ch) => write!(f, "{}", ch),
+            KeyCode::Enter => f.write_str("enter"),
+            KeyCode::Esc => f.write_str("esc"),
+            KeyCode::Tab => f.write_str("tab"),
+            KeyCode::BackTab => f.write_str("btab"),
+            KeyCode::Backspace => f.write_str("bspace"),
+            KeyCode::Up => f.write_str("up"),
+            KeyCode::Down => f.write_str("down"),
+            KeyCode::Left => f.write_str("left"),
+            KeyCode::Right => f.write_str("right"),
+            KeyCode::Home => f.write_str("home"),
+            KeyCode::End => f.write_str("end"),
+            KeyCode::PageUp => f.write_str("pgup"),
+            KeyCode::PageDown => f.write_str("pgdn"),
+            KeyCode::Delete => f.write_str("del"),
+            KeyCode::Insert => f.write_str("insert"),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Key {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        key.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Actions that can be bound to keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum Action {
+    /// Move the highlight to the previous item, or recall an older query
+    /// from the history when nothing matches.
+    SelectPrev,
+    /// Move the highlight to the next item, or recall a newer query from
+    /// the history when nothing matches.
+    SelectNext,
+    /// Accept the highlighted item. Does nothing while no item matches.
+    Accept,
+    /// Accept the query itself rather than an item, returned as
+    /// `PickOutcome::Query`. Does nothing while the query is empty.
+    AcceptQuery,
+    /// Accept the visible item numbered `n` (1 to 10) by quick-select, see
+    /// [`PickerState::set_quick_select`](crate::PickerState::set_quick_select).
+    QuickSelect(usize),
+    /// Move the highlight to the visible item numbered `n`, counting
+    /// from 1 at the top of the list.
+    JumpTo(usize),
+    /// Move the highlight to the first match.
+    SelectFirst,
+    /// Move the highlight to the last match.
+    SelectLast,
+    /// Cancel the selection.
+    Cancel,
+    /// Mark or unmark the highlighted item and move to the next one, in
+    /// [`FuzzyPicker::pick_multi`](crate::FuzzyPicker::pick_multi).
+    ToggleMark,
+    /// Mark every matching item in `pick_multi()`.
+    MarkAll,
+    /// Unmark every item in `pick_multi()`.
+    UnmarkAll,
+    /// Mark the matching items that are unmarked and unmark the others
+    /// in `pick_multi()`.
+    InvertMarks,
+    /// Cancel the selection if the query is empty, otherwise type the
+    /// key's character, e.g. to quit with `q`.
+    CancelOnEmptyQuery,
+    /// Delete the last character of the query, or go back up a level
+    /// when drilling down with an empty query.
+    DeleteChar,
+    /// Delete the last word of the query.
+    DeleteWord,
+    /// Replace the query with the longest common prefix of the matching
+    /// items, or with the highlighted item when that would not extend it.
+    Complete,
+    /// Jump to the next item containing the last word of the query.
+    FindNext,
+    /// Scroll all items to the left.
+    ScrollLeft,
+    /// Scroll all items to the right, revealing the rest of long items.
+    ScrollRight,
+    /// Move the focus of the column actions to the next visible column,
+    /// whose title is underlined, see
+    /// [`PickerState::set_columns`](crate::PickerState::set_columns).
+    NextColumn,
+    /// Widen the focused column, or the first one if none is focused.
+    WidenColumn,
+    /// Narrow the focused column, cutting off longer values.
+    NarrowColumn,
+    /// Hide the focused column and focus the next one. The last visible
+    /// column stays.
+    HideColumn,
+    /// Show the hidden columns again.
+    ShowColumns,
+    /// Replace the query with an older one from the history.
+    HistoryPrev,
+    /// Replace the query with a newer one from the history.
+    HistoryNext,
+    /// Switch to the next match mode: fuzzy, exact, prefix, then regex
+    /// with the `regex` feature, and back to fuzzy.
+    CycleMatchMode,
+    /// Replace the items with those produced by the reload callback, see
+    /// [`PickerState::set_reload`](crate::PickerState::set_reload).
+    Reload,
+    /// Switch the accessible mode on or off, see
+    /// [`PickerState::set_accessible`](crate::PickerState::set_accessible).
+    ToggleAccessible,
+    /// Show a list of the key bindings until the next key press.
+    ToggleHelp,
+    /// Scroll the preview pane up by a line.
+    PreviewUp,
+    /// Scroll the preview pane down by a line.
+    PreviewDown,
+    /// Turn the query into a filter chip shown before it, so the next
+    /// query searches within its matches. `Backspace` on an empty query or
+    /// a click on a chip removes it again.
+    PromoteQuery,
+    /// Make the current matches the items searched by the next query,
+    /// clearing the query. Does nothing while the query is empty.
+    Narrow,
+    /// Search all items again after `Narrow`.
+    Widen,
+    /// Hide or show the preview pane, see
+    /// [`PickerState::set_preview_hidden`](crate::PickerState::set_preview_hidden).
+    TogglePreview,
+    /// Suspend the process, handing the terminal back to the shell until
+    /// it is resumed with `fg`. Only supported by `pick()` on Unix.
+    Suspend,
+}
+
+impl fmt::Display for Action {
+    /// Writes the action's name in kebab case, as used in configuration
+    /// files.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::SelectPrev => "select-prev",
+            Action::SelectNext => "select-next",
+            Action::Accept => "accept",
+            Action::AcceptQuery => "accept-query",
+            Action::QuickSelect(n) => return write!(f, "quick-select {}", n),
+            Action::JumpTo(n) => return write!(f, "jump-to {}", n),
+            Action::SelectFirst => "select-first",
+            Action::SelectLast => "select-last",
+            Action::Cancel => "cancel",
+            Action::ToggleMark => "toggle-mark",
+            Action::MarkAll => "mark-all",
+            Action::UnmarkAll => "unmark-all",
+            Action::InvertMarks => "invert-marks",
+            Action::CancelOnEmptyQuery => "cancel-on-empty-query",
+            Action::DeleteChar => "delete-char",
+            Action::DeleteWord => "delete-word",
+            Action::Complete => "complete",
+            Action::FindNext => "find-next",
+            Action::ScrollLeft => "scroll-left",
+            Action::ScrollRight => "scroll-right",
+            Action::NextColumn => "next-column",
+            Action::WidenColumn => "widen-column",
+            Action::NarrowColumn => "narrow-column",
+            Action::HideColumn => "hide-column",
+            Action::ShowColumns => "show-columns",
+            Action::HistoryPrev => "history-prev",
+            Action::HistoryNext => "history-next",
+            Action::CycleMatchMode => "cycle-match-mode",
+            Action::Reload => "reload",
+            Action::ToggleAccessible => "toggle-accessible",
+            Action::ToggleHelp => "toggle-help",
+            Action::PreviewUp => "preview-up",
+            Action::PreviewDown => "preview-down",
+            Action::TogglePreview => "toggle-preview",
+            Action::Suspend => "suspend",
+            Action::PromoteQuery => "promote-query",
+            Action::Narrow => "narrow",
+            Action::Widen => "widen",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Mapping from keys to the actions they trigger.
+pub(crate) struct Keymap {
+    bindings: HashMap<Key, Action>,
+}
+
+impl Keymap {
+    pub(crate) fn get(&self, key: &Key) -> Option<Action> {
+        self.bindings.get(key).copied()
+    }
+
+    pub(crate) fn bind(&mut self, key: Key, action: Action) {
+        self.bindings.insert(key, action);
+    }
+
+    pub(crate) fn unbind(&mut self, key: &Key) {
+        self.bindings.remove(key);
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Key, &Action)> {
+        self.bindings.iter()
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut bindings = HashMap::from([
+            (Key::plain(KeyCode::Up), Action::SelectPrev),
+            (Key::plain(KeyCode::Left), Action::SelectPrev),
+            (Key::ctrl('p'), Action::SelectPrev),
+            (Key::plain(KeyCode::Down), Action::SelectNext),
+            (Key::plain(KeyCode::Right), Action::SelectNext),
+            (Key::ctrl('n'), Action::SelectNext),
+            (Key::plain(KeyCode::Enter), Action::Accept),
+            (Key::plain(KeyCode::Esc), Action::Cancel),
+            (Key::ctrl('g'), Action::Cancel),
+            (Key::ctrl('q'), Action::Cancel),
+            (Key::plain(KeyCode::Backspace), Action::DeleteChar),
+            (Key::new(KeyCode::Backspace, KeyModifiers::ALT), Action::DeleteWord),
+            (Key::ctrl('w'), Action::DeleteWord),
+            (Key::ctrl('f'), Action::FindNext),
+            (Key::plain(KeyCode::Tab), Action::Complete),
+            (Key::new(KeyCode::Left, KeyModifiers::SHIFT), Action::ScrollLeft),
+            (Key::new(KeyCode::Right, KeyModifiers::SHIFT), Action::ScrollRight),
+            (Key::alt('c'), Action::NextColumn),
+            (Key::new(KeyCode::Right, KeyModifiers::ALT), Action::WidenColumn),
+            (Key::new(KeyCode::Left, KeyModifiers::ALT), Action::NarrowColumn),
+            (Key::alt('h'), Action::HideColumn),
+            (Key::alt('s'), Action::ShowColumns),
+            (Key::new(KeyCode::Up, KeyModifiers::ALT), Action::HistoryPrev),
+            (Key::new(KeyCode::Down, KeyModifiers::ALT), Action::HistoryNext),
+            (Key::ctrl('s'), Action::CycleMatchMode),
+            (Key::ctrl('r'), Action::Reload),
+            (Key::plain(KeyCode::BackTab), Action::ToggleMark),
+            (Key::ctrl(' '), Action::ToggleMark),
+            (Key::ctrl('a'), Action::MarkAll),
+            (Key::ctrl('d'), Action::UnmarkAll),
+            (Key::ctrl('t'), Action::InvertMarks),
+            (Key::plain(KeyCode::F(1)), Action::ToggleHelp),
+            (Key::new(KeyCode::Up, KeyModifiers::SHIFT), Action::PreviewUp),
+            (Key::new(KeyCode::Down, KeyModifiers::SHIFT), Action::PreviewDown),
+            (Key::alt('p'), Action::TogglePreview),
+            (Key::new(KeyCode::Enter, KeyModifiers::ALT), Action::PromoteQuery),
+            (Key::alt('n'), Action::Narrow),
+            (Key::alt('w'), Action::Widen),
+            (Key::ctrl('z'), Action::Suspend),
+            (Key::new(KeyCode::Home, KeyModifiers::CONTROL), Action::SelectFirst),
+            (Key::new(KeyCode::End, KeyModifiers::CONTROL), Action::SelectLast),
+        ]);
+        for n in 1..=9 {
+            bindings.insert(Key::alt(char::from_digit(n as u32, 10).unwrap_or('0')), Action::JumpTo(n));
+        }
+        Self { bindings }
+    }
+}
@@ -26,271 +26,3387 @@
 //! }
 //! ```
 
-use std::io::{Stdout, stdout, Write};
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufRead, BufReader, Read};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
 use std::fmt::Display;
 use std::clone::Clone;
-use std::time::Duration;
-use std::error::Error;
-use crossterm::{
-    QueueableCommand, 
-    cursor::{MoveTo}, 
-    style::{Stylize, Print, PrintStyledContent},
-    terminal::{
-        self, Clear, ClearType, 
-        EnterAlternateScreen, LeaveAlternateScreen
-    },
-    event::{
-        poll, read, Event, KeyCode, KeyEventKind, 
-        EnableMouseCapture, DisableMouseCapture,
-        MouseEventKind, MouseButton
-    }
-};
-use fuzzy_matcher::FuzzyMatcher;
+use std::time::{Duration, Instant};
+use crossterm::event::{KeyEventKind, MouseEventKind, MouseButton};
 use fuzzy_matcher::skim::SkimMatcherV2;
 
-/// Struct representing a fuzzy picker for interactive item selection.
-pub struct FuzzyPicker<T: Display + Clone> {
-    stdout: Stdout, 
+mod ansi;
+mod backend;
+mod columns;
+pub mod compat;
+mod entry;
+#[cfg(feature = "serde")]
+mod config;
+mod error;
+mod frame;
+mod history;
+mod keys;
+mod matching;
+mod options;
+mod preview;
+mod session;
+#[cfg(feature = "syntect")]
+mod syntax;
+mod terminal;
+mod text;
+mod theme;
+#[cfg(feature = "ratatui")]
+mod widget;
+#[cfg(test)]
+mod tests;
+
+pub use backend::{Backend, CrosstermBackend};
+pub use columns::{Align, Column, FieldSpec, ParseFieldSpecError};
+pub use entry::Entry;
+#[cfg(feature = "serde")]
+pub use config::{ColumnConfig, PickerConfig};
+pub use error::PickError;
+pub use keys::{Action, Key, ParseKeyError};
+pub use matching::{MatchMode, RankingMode};
+pub use options::DEFAULT_OPTS_ENV;
+pub use preview::{looks_binary, sanitize_preview, PreviewPosition};
+pub use session::SessionHandle;
+#[cfg(feature = "syntect")]
+pub use syntax::SyntaxPreview;
+pub use terminal::{FuzzyPicker, MouseMode, TtyFallback};
+pub use theme::HighlightMode;
+#[cfg(feature = "ratatui")]
+pub use widget::FuzzyPickerWidget;
+pub use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+pub use crossterm::style::ContentStyle;
+use columns::ColumnStore;
+use history::{History, DEFAULT_HISTORY_SIZE};
+use keys::Keymap;
+use matching::{MatchCache, Pattern, Ranker};
+use preview::Preview;
+#[cfg(feature = "preview-command")]
+use preview::PreviewJob;
+use theme::Indicators;
+
+/// How long to wait for input before checking for streamed items.
+const STREAM_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long to wait for input when nothing is streaming in.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long to wait for input while items are matched in the background.
+const MATCH_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Frames of the spinner shown while items are loading.
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// How long each spinner frame is shown.
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Terminal size assumed when it cannot be queried, e.g. on a Windows
+/// console whose handles are redirected.
+const FALLBACK_TERMINAL_SIZE: (u16, u16) = (80, 24);
+
+/// Number of items matched between checks for new input, which cancels a
+/// stale match while typing.
+const MATCH_CHUNK_SIZE: usize = 10_000;
+
+/// First line of the key binding help.
+const HELP_TITLE: &str = "key bindings (press any key to close)";
+
+/// Number of columns moved by a single horizontal scroll.
+const HORIZONTAL_SCROLL_STEP: usize = 8;
+
+/// Number of characters a column is widened or narrowed by at a time.
+const COLUMN_RESIZE_STEP: usize = 2;
+
+/// Narrowest terminal, in columns, that gets the full layout. Narrower
+/// terminals, and those without a row to spare for items, only get the
+/// prompt line.
+const MIN_FULL_WIDTH: usize = 16;
+
+/// Path of the controlling terminal on this platform, for use with
+/// [`FuzzyPicker::set_tty_path`].
+#[cfg(windows)]
+pub const DEFAULT_TTY_PATH: &str = "CONOUT$";
+/// Path of the controlling terminal on this platform, for use with
+/// [`FuzzyPicker::set_tty_path`].
+#[cfg(not(windows))]
+pub const DEFAULT_TTY_PATH: &str = "/dev/tty";
+
+/// Placement of the prompt and the item list on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum Layout {
+    /// Prompt at the top, items listed downward.
+    #[default]
+    Default,
+    /// Prompt at the bottom, items listed upward, like fzf.
+    Reverse,
+}
+
+/// Terminal features detected while picking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities {
+    /// Whether mouse capture was enabled. When it is not, the picker
+    /// continues with keyboard input only.
+    pub mouse_capture: bool,
+    /// Whether bracketed paste was enabled, so pasted text arrives as a
+    /// whole instead of as separate key presses.
+    pub bracketed_paste: bool,
+    /// Whether colors are used. They are not when the `NO_COLOR`
+    /// environment variable is set or the terminal is `dumb`, in which
+    /// case the highlighted item is shown bold and underlined.
+    pub colors: bool,
+}
+
+/// How a call to [`FuzzyPicker::pick_outcome`] ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PickOutcome<T> {
+    /// An item was accepted with `key`, one of the keys bound to
+    /// `Action::Accept`. A mouse double-click is reported as `Enter`.
+    Picked { item: T, key: Key },
+    /// The selection was cancelled with `key`, one of the keys bound to
+    /// `Action::Cancel` or `Action::CancelOnEmptyQuery`.
+    Cancelled { key: Key },
+    /// Nothing was pressed within the time set with
+    /// [`FuzzyPicker::set_timeout`]. Holds the highlighted item if
+    /// [`FuzzyPicker::set_accept_on_timeout`] is enabled.
+    TimedOut { item: Option<T> },
+    /// The query itself was accepted with `key`, either bound to
+    /// `Action::AcceptQuery` or, with [`PickerState::set_accept_query`],
+    /// `Enter` while nothing matches.
+    Query { query: String, key: Key },
+    /// There were no items to pick from, with
+    /// [`PickerState::set_exit_on_empty`] enabled.
+    Empty,
+}
+
+impl<T> PickOutcome<T> {
+    /// Returns the accepted item, if any.
+    pub fn into_item(self) -> Option<T> {
+        match self {
+            PickOutcome::Picked { item, .. } => Some(item),
+            PickOutcome::Cancelled { .. } => None,
+            PickOutcome::TimedOut { item } => item,
+            PickOutcome::Query { .. } | PickOutcome::Empty => None,
+        }
+    }
+
+    /// Returns a reference to the accepted item, if any.
+    pub fn item(&self) -> Option<&T> {
+        match self {
+            PickOutcome::Picked { item, .. } => Some(item),
+            PickOutcome::Cancelled { .. } => None,
+            PickOutcome::TimedOut { item } => item.as_ref(),
+            PickOutcome::Query { .. } | PickOutcome::Empty => None,
+        }
+    }
+}
+
+/// The state of a running picker, passed to the footer callback set with
+/// [`PickerState::set_footer`].
+#[derive(Debug)]
+pub struct FooterInfo<'a, T> {
+    /// The current query.
+    pub query: &'a str,
+    /// The number of items matching the query.
+    pub matched: usize,
+    /// The total number of items.
+    pub total: usize,
+    /// The highlighted item, if any.
+    pub selected: Option<&'a T>,
+}
+
+/// Statistics of a matching pass, passed to the callback set with
+/// [`PickerState::set_on_match`].
+#[derive(Debug, Clone)]
+pub struct MatchMetrics<'a> {
+    /// The query that was matched.
+    pub query: &'a str,
+    /// The time taken to match and rank the items.
+    pub duration: Duration,
+    /// The number of items checked against the query. When the query was
+    /// extended, only the matches of the shorter query are checked.
+    pub scanned: usize,
+    /// The number of matching items.
+    pub matched: usize,
+}
+
+/// A row of the item list, as returned by [`PickerState::visible_rows`].
+#[derive(Debug)]
+pub struct VisibleRow<'a, T> {
+    /// The item shown in the row, or `None` for a group header.
+    pub item: Option<&'a T>,
+    /// The text shown in the row, without ANSI escapes.
+    pub text: String,
+    /// Whether the row is highlighted.
+    pub selected: bool,
+    /// Whether the item is marked for `pick_multi()`.
+    pub marked: bool,
+    /// Whether the item is disabled.
+    pub disabled: bool,
+}
+
+
+
+/// The effect of an event on the picker.
+enum Handled<T> {
+    /// The picker changed and must be redrawn.
+    Redraw,
+    /// The event was ignored.
+    Unchanged,
+    /// The selection ended.
+    Done(PickOutcome<T>),
+    /// The user asked to suspend the process.
+    Suspend,
+    /// The command bound to the key with `run_external()` is to be run on
+    /// the highlighted item.
+    RunExternal(Key),
+}
+
+/// An item matching the current query.
+struct Match {
+    /// Index of the item in the picker's item list.
+    index: usize,
+    /// The group's name if this is a section header rather than an item.
+    /// A header's `index` is that of the group's first matching item.
+    header: Option<String>,
+}
+
+/// Strings derived from an item once, rather than on every keystroke.
+struct ItemText {
+    /// The item's display string, produced when first needed if the item
+    /// is matched against a key set with `set_match_key()`.
+    display: OnceCell<String>,
+    /// The lowercase text the query is matched against, shared with
+    /// background matching.
+    searchable: Arc<str>,
+    /// Whether an earlier item has the same display string, with
+    /// `set_dedupe()` enabled.
+    duplicate: bool,
+}
+
+/// A match of the items against the query whose candidates are being
+/// scored.
+struct MatchJob {
+    /// The lowercase query.
+    query: String,
+    ranking_mode: RankingMode,
+    /// Number of items when matching started.
+    num_of_items: usize,
+    /// Indexes of the items being scored, in ascending order.
+    candidates: Vec<usize>,
+    started: Instant,
+}
+
+/// The highlighted and marked items, by their `set_item_id()` ids, to be
+/// found again after the item list was replaced.
+struct TrackedItems {
+    highlighted: Option<u64>,
+    marked: HashSet<u64>,
+}
+
+/// A level of the item tree left by descending into one of its items.
+struct Level<T> {
+    items: Vec<T>,
+    query: String,
+    /// The item descended into.
+    parent: T,
+    /// Index of the parent in `items`.
+    index: usize,
+}
+
+/// Callback producing a line of text for an item.
+type ItemFormatter<T> = Box<dyn Fn(&T) -> String>;
+
+/// Callback producing the footer line from the picker's state.
+type FooterFormatter<T> = Box<dyn Fn(&FooterInfo<T>) -> String>;
+
+/// Callback mutating the item list, given the index of the highlighted item.
+type ItemAction<T> = Box<dyn FnMut(&mut Vec<T>, Option<usize>)>;
+
+/// Callback producing a short, possibly styled, prefix for an item.
+type ItemDecoration<T> = Box<dyn Fn(&T) -> String>;
+
+/// Callback adjusting an item's match score.
+type ScoreAdjust<T> = Box<dyn Fn(&T, i64) -> i64>;
+
+/// Callback naming the group an item belongs to.
+type ItemGroup<T> = Box<dyn Fn(&T) -> String>;
+
+/// Callback producing the children of an item, or `None` for a leaf.
+type ItemChildren<T> = Box<dyn FnMut(&T) -> Option<Vec<T>>>;
+
+/// Callback producing a fresh item list.
+type ItemLoader<T> = Box<dyn FnMut() -> Vec<T>>;
+
+/// Callback notified of an item.
+type ItemCallback<T> = Box<dyn FnMut(&T)>;
+
+/// Callback notified of a finished matching pass.
+type MatchCallback = Box<dyn FnMut(&MatchMetrics)>;
+
+/// Predicate over an item and its index in the item list.
+type ItemPredicate<T> = Box<dyn Fn(usize, &T) -> bool>;
+
+/// Hash of an item's stable identity.
+type ItemId<T> = Box<dyn Fn(&T) -> u64>;
+
+/// The state of a fuzzy picker: its items, query, matches, selection and
+/// viewport, without any terminal I/O.
+///
+/// [`FuzzyPicker`] drives a `PickerState` from the terminal and derefs to
+/// it, so all settings are available on both. Use a `PickerState` directly
+/// to run the picker headless, e.g. in tests or behind another frontend,
+/// by feeding it events and reading back
+/// [`visible_rows`](Self::visible_rows).
+///
+/// # Example
+///
+/// ```
+/// use fuzzypicker::{KeyCode, PickOutcome, PickerState};
+///
+/// let mut state = PickerState::new(&["apple", "banana", "cherry"]);
+/// state.handle_key(KeyCode::Char('b').into());
+/// let rows: Vec<String> = state.visible_rows().into_iter().map(|row| row.text).collect();
+/// assert_eq!(rows, ["banana"]);
+/// let outcome = state.handle_key(KeyCode::Enter.into());
+/// assert_eq!(outcome.and_then(PickOutcome::into_item), Some("banana"));
+/// ```
+pub struct PickerState<T: Display + Clone> {
     matcher: SkimMatcherV2,
+    keymap: Keymap,
+    item_actions: HashMap<Key, ItemAction<T>>,
+    external_commands: HashMap<Key, ItemCallback<T>>,
     items: Vec<T>, 
-    display_items: Vec<String>, 
+    item_source: Option<Receiver<T>>,
+    reload: Option<ItemLoader<T>>,
+    children: Option<ItemChildren<T>>,
+    levels: Vec<Level<T>>,
+    reload_interval: Option<Duration>,
+    last_reload: Instant,
+    started: Instant,
+    drawn_spinner: Option<char>,
+    matches: Vec<Match>,
+    match_cache: MatchCache,
+    item_texts: Vec<ItemText>,
+    group_by: Option<ItemGroup<T>>,
+    decoration: Option<ItemDecoration<T>>,
+    decoration_indent: usize,
+    line_wrap: bool,
+    wrap: bool,
+    quick_select: bool,
+    multi_select: bool,
+    marked: HashSet<usize>,
+    indicators: Indicators,
+    max_selections: Option<usize>,
+    selection_limit_hit: bool,
     num_of_items: usize,
+    num_of_matches: usize,
     num_of_displayable_items: usize,
     prompt: String,
+    chips: Vec<(String, MatchMode)>,
+    narrowed: Option<Vec<usize>>,
     debug: String,
     selected: usize, 
     start_index: usize, 
     end_index: usize,
     height: usize,
+    width: usize,
+    scroll_offset: usize,
+    ansi: bool,
+    ranking_mode: RankingMode,
+    match_mode: MatchMode,
+    score_adjust: Option<ScoreAdjust<T>>,
+    query_error: Option<String>,
+    preserve_order_threshold: usize,
+    max_results: Option<usize>,
+    debounce: Duration,
+    pending_filter: Option<Instant>,
+    /// Whether edits to the query are matched on a background thread, as
+    /// `pick()` does so typing stays responsive.
+    background_matching: bool,
+    ranker: Ranker,
+    /// The match running in the background, if any.
+    match_job: Option<MatchJob>,
+    layout: Layout,
+    double_click_interval: Duration,
+    last_click: Option<(Instant, usize)>,
+    /// Rows requested with `set_height()`. The picker's `height` is
+    /// smaller while the terminal has fewer rows.
+    inline_height: Option<u16>,
+    top: u16,
+    /// Screen column of the picker's left edge, when drawn as a widget.
+    left: u16,
+    scroll_step: usize,
+    scrolloff: usize,
+    prompt_label: String,
+    status_text: String,
+    session: SessionHandle,
+    history: Option<History>,
+    history_size: usize,
+    initial_selection: Option<ItemPredicate<T>>,
+    disabled: Option<ItemPredicate<T>>,
+    confirmation: Option<ItemPredicate<T>>,
+    confirming: Option<(T, Key)>,
+    header: Vec<String>,
+    footer: Option<FooterFormatter<T>>,
+    empty_message: String,
+    no_match_message: String,
+    columns: Vec<Column>,
+    delimiter: Option<String>,
+    nth: Option<FieldSpec>,
+    match_key: Option<ItemFormatter<T>>,
+    item_id: Option<ItemId<T>>,
+    dedupe: bool,
+    /// Display strings seen so far, with `set_dedupe()` enabled.
+    seen_displays: HashSet<String>,
+    column_widths: Vec<usize>,
+    /// Column resized or hidden by the column actions, once one was used.
+    column_focus: Option<usize>,
+    /// Widths and visibility set by the configuration, by column header.
+    column_defaults: HashMap<String, (Option<usize>, Option<bool>)>,
+    column_store: Option<ColumnStore>,
+    accessible: bool,
+    auto_accept_single: bool,
+    auto_offered: Option<usize>,
+    exit_on_empty: bool,
+    accept_query: bool,
+    help_visible: bool,
+    on_highlight: Option<ItemCallback<T>>,
+    on_accept: Option<ItemCallback<T>>,
+    tooltip: Option<ItemFormatter<T>>,
+    on_match: Option<MatchCallback>,
+    hovered: Option<(usize, u16)>,
+    announced: Option<usize>,
+    preview: Option<Preview<T>>,
+    preview_lines: Vec<String>,
+    previewed: Option<usize>,
+    preview_scroll: usize,
+    preview_position: PreviewPosition,
+    preview_size: u16,
+    preview_hidden: bool,
+    #[cfg(feature = "preview-command")]
+    preview_job: Option<PreviewJob>,
+    active: bool,
 }
 
-impl<T: Display + Clone> FuzzyPicker<T> {
-    /// Constructs a new `FuzzyPicker` instance with the given list of items.
+impl<T: Display + Clone> PickerState<T> {
+    /// Constructs a new `PickerState` with the given list of items, sized
+    /// to a standard 80x24 terminal until [`resize`](Self::resize)d.
     ///
     /// # Arguments
     ///
     /// * `items` - A slice of items implementing `Display + Clone`.
-    ///
-    /// # Returns
-    ///
-    /// A new `FuzzyPicker` instance.
     pub fn new(items: &[T]) -> Self {
-        let (_, h) = terminal::size().unwrap();
-        let list_items = items.to_vec();
+        Self::from_items(items.iter().cloned())
+    }
+
+    /// Constructs a new `PickerState` that takes ownership of `items`,
+    /// which can come from any iterator.
+    pub fn from_items(items: impl IntoIterator<Item = T>) -> Self {
+        let (w, h) = FALLBACK_TERMINAL_SIZE;
+        let list_items: Vec<T> = items.into_iter().collect();
         let num_of_items = list_items.len();
-        let num_of_displayable_items = num_of_items.min((h-1) as usize);
+        let num_of_displayable_items = num_of_items.min(h.saturating_sub(1) as usize);
         Self {
-            stdout: stdout(), 
             matcher: SkimMatcherV2::default(),
+            keymap: Keymap::default(),
+            item_actions: HashMap::new(),
+            external_commands: HashMap::new(),
             items: list_items, 
-            display_items: Vec::<String>::new(),
+            item_source: None,
+            reload: None,
+            children: None,
+            levels: Vec::new(),
+            reload_interval: None,
+            last_reload: Instant::now(),
+            started: Instant::now(),
+            drawn_spinner: None,
+            matches: Vec::new(),
+            group_by: None,
+            decoration: None,
+            decoration_indent: 0,
+            line_wrap: false,
+            wrap: true,
+            quick_select: false,
+            multi_select: false,
+            marked: HashSet::new(),
+            indicators: Indicators::default(),
+            max_selections: None,
+            selection_limit_hit: false,
             num_of_items,
+            num_of_matches: num_of_items,
             num_of_displayable_items,
             prompt: String::new(), 
+            chips: Vec::new(),
+            narrowed: None,
             debug: String::new(), 
             selected: 0,
             start_index: 0, 
-            end_index: num_of_displayable_items - 1,
-            height: h as usize
+            end_index: num_of_displayable_items.saturating_sub(1),
+            height: h as usize,
+            width: w as usize,
+            scroll_offset: 0,
+            ansi: false,
+            ranking_mode: RankingMode::default(),
+            match_mode: MatchMode::default(),
+            score_adjust: None,
+            query_error: None,
+            preserve_order_threshold: 0,
+            max_results: None,
+            debounce: Duration::ZERO,
+            pending_filter: None,
+            background_matching: false,
+            ranker: Ranker::default(),
+            match_job: None,
+            layout: Layout::default(),
+            double_click_interval: Duration::from_millis(500),
+            last_click: None,
+            inline_height: None,
+            top: 0,
+            left: 0,
+            scroll_step: 1,
+            scrolloff: 0,
+            prompt_label: String::from("> "),
+            status_text: String::new(),
+            session: SessionHandle::default(),
+            history: None,
+            history_size: DEFAULT_HISTORY_SIZE,
+            initial_selection: None,
+            disabled: None,
+            confirmation: None,
+            confirming: None,
+            header: Vec::new(),
+            footer: None,
+            empty_message: String::from("no items"),
+            no_match_message: String::from("no matches"),
+            columns: Vec::new(),
+            delimiter: None,
+            nth: None,
+            match_key: None,
+            item_id: None,
+            dedupe: false,
+            seen_displays: HashSet::new(),
+            column_widths: Vec::new(),
+            column_focus: None,
+            column_defaults: HashMap::new(),
+            column_store: None,
+            match_cache: MatchCache::default(),
+            item_texts: Vec::new(),
+            accessible: false,
+            auto_accept_single: false,
+            auto_offered: None,
+            exit_on_empty: false,
+            accept_query: false,
+            help_visible: false,
+            on_highlight: None,
+            on_accept: None,
+            tooltip: None,
+            on_match: None,
+            hovered: None,
+            announced: None,
+            preview: None,
+            preview_lines: Vec::new(),
+            previewed: None,
+            preview_scroll: 0,
+            preview_position: PreviewPosition::default(),
+            preview_size: 50,
+            preview_hidden: false,
+            #[cfg(feature = "preview-command")]
+            preview_job: None,
+            active: false,
         }
     }
-    
-    fn prev_item(&mut self) {
-        if self.num_of_items <= 0 { return; }
-        if self.selected == 0 {
-            self.selected = self.num_of_items - 1;
-        } else {
-            self.selected -= 1;
+
+    /// Replaces the items with those produced by an iterator, e.g. to reuse
+    /// the picker's settings for another selection. Marks, narrowing and
+    /// drill-down levels are cleared, and the highlight returns to the top.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The new items, e.g. from `lines()` or a directory walker.
+    pub fn set_items_iter(&mut self, items: impl IntoIterator<Item = T>) {
+        self.items = items.into_iter().collect();
+        self.marked.clear();
+        self.levels.clear();
+        self.invalidate_item_texts();
+        if self.active {
+            self.pending_filter = None;
+            self.filter_by_prompt();
+            self.reset_scroll();
+            self.clamp_viewport();
         }
+    }
+    
+    /// Highlights the item at `index` in the item list when picking starts,
+    /// e.g. the currently checked-out branch in a branch picker.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the item in the list passed to `new()`.
+    pub fn set_initial_selection(&mut self, index: usize) {
+        self.initial_selection = Some(Box::new(move |i, _| i == index));
+    }
 
-        if self.selected < self.start_index {
-            self.start_index = self.selected;
-            self.end_index = self.start_index + self.num_of_displayable_items - 1;
-        } else if self.selected > self.end_index {
-            self.end_index = self.selected;
-            self.start_index = self.end_index - self.num_of_displayable_items + 1;
+    /// Highlights the first item satisfying `predicate` when picking starts.
+    ///
+    /// With streamed items, the selection moves to the first matching item
+    /// as it arrives, unless the user has already moved the highlight.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Returns `true` for the item to highlight.
+    pub fn set_initial_selection_by(&mut self, predicate: impl Fn(&T) -> bool + 'static) {
+        self.initial_selection = Some(Box::new(move |_, item| predicate(item)));
+    }
+
+    /// Marks items satisfying `predicate` as disabled: they are shown
+    /// dimmed, skipped by keyboard navigation and cannot be accepted, e.g.
+    /// options that are unavailable in the current context.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Returns `true` for items to disable.
+    pub fn set_disabled_by(&mut self, predicate: impl Fn(&T) -> bool + 'static) {
+        self.disabled = Some(Box::new(move |_, item| predicate(item)));
+    }
+
+    /// Asks "Accept '<item>'? (y/n)" before returning an accepted item,
+    /// e.g. when picking a branch to delete.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether every pick must be confirmed. Defaults to `false`.
+    pub fn require_confirmation(&mut self, enabled: bool) {
+        self.confirmation = if enabled { Some(Box::new(|_, _| true)) } else { None };
+    }
+
+    /// Asks for confirmation before returning accepted items satisfying
+    /// `predicate`, see `require_confirmation()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Returns `true` for items that need confirming.
+    pub fn set_confirmation_by(&mut self, predicate: impl Fn(&T) -> bool + 'static) {
+        self.confirmation = Some(Box::new(move |_, item| predicate(item)));
+    }
+
+    /// Remembers accepted queries in the file at `path`, one per line.
+    ///
+    /// Previous queries are recalled with `Alt-Up`/`Alt-Down`, or with the
+    /// item navigation keys (e.g. `Ctrl-P`/`Ctrl-N`) while nothing matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The history file. It is created on the first accept. If
+    ///   it cannot be read, the picker starts with an empty history.
+    pub fn set_history_file(&mut self, path: impl Into<PathBuf>) {
+        self.history = Some(History::new(path.into(), self.history_size));
+    }
+
+    /// Sets the maximum number of queries kept in the history file.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - The number of queries to keep. Defaults to 1000.
+    pub fn set_history_size(&mut self, size: usize) {
+        self.history_size = size;
+        if let Some(history) = &mut self.history {
+            history.set_size(size);
         }
     }
 
-    fn next_item(&mut self) {
-        if self.num_of_items <= 0 { return; }
-        self.selected = (self.selected + 1) % self.num_of_items;
-        if self.selected == 0 {
-            self.start_index = 0;
-            self.end_index = self.num_of_displayable_items - 1;
-        } else if self.selected > self.end_index {
-            self.start_index += 1;
-            self.end_index += 1;
-        }        
+    /// Binds a key to an action, replacing any existing binding for that key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key that triggers the action.
+    /// * `action` - The action to perform.
+    pub fn bind(&mut self, key: Key, action: Action) {
+        self.keymap.bind(key, action);
     }
 
-    fn reset_scroll(&mut self) {
-        self.start_index = 0;
-        self.selected = self.start_index;
+    /// Removes the binding for a key, if any.
+    ///
+    /// Unbound character keys are typed into the query.
+    pub fn unbind(&mut self, key: Key) {
+        self.keymap.unbind(&key);
     }
-    
-    /// Initiates the interactive item selection process.
+
+    /// Binds a key to a callback that edits the item list while the picker
+    /// is open, e.g. to delete the highlighted entry. The items are matched
+    /// again after the callback returns. Takes precedence over `bind()`.
     ///
-    /// Handles keyboard and mouse events to perform fuzzy search, selection,
-    /// and navigation within the item list.
+    /// # Arguments
     ///
-    /// # Returns
+    /// * `key` - The key that runs the callback.
+    /// * `action` - Receives the item list and the index of the
+    ///   highlighted item in it, if any.
     ///
-    /// `Ok(Some(selected_item))` if an item is selected,
-    /// `Ok(None)` if selection is cancelled,
-    /// `Err(Box<dyn Error>)` for any error encountered during selection.
-    pub fn pick(&mut self) -> Result<Option<T>, Box<dyn Error>> {
-        self.filter_by_prompt();
-        let mut picked_item: Option<T> = None;
-        terminal::enable_raw_mode()?;
-        self.stdout
-            .queue(EnterAlternateScreen)?
-            .queue(EnableMouseCapture)?;
-        loop {
-            if poll(Duration::from_millis(500))? {
-                match read()? {
-                    Event::Key(event) => {
-                        if event.kind == KeyEventKind::Press {
-                            match event.code {
-                                KeyCode::Char(ch) => {
-                                    self.prompt.push(ch);
-                                    self.filter_by_prompt();
-                                    self.reset_scroll();
-                                },
-                                KeyCode::Backspace => {
-                                    self.prompt.pop();
-                                    self.filter_by_prompt();
-                                    self.reset_scroll();
-                                }
-                                KeyCode::Esc => {
-                                    self.stdout
-                                        .queue(LeaveAlternateScreen)?
-                                        .queue(DisableMouseCapture)?;
-                                    break;
-                                },
-                                KeyCode::Up | KeyCode::Left => {
-                                    self.prev_item();
-                                },
-                                KeyCode::Down | KeyCode::Right => {
-                                    self.next_item();
-                                },
-                                KeyCode::Enter => {
-                                    self.stdout
-                                        .queue(LeaveAlternateScreen)?
-                                        .queue(DisableMouseCapture)?;
-                                    picked_item = self.items.iter().find(
-                                        |&item| format!("{item}") == self.display_items[self.selected]
-                                    ).cloned();
-                                    break;
-                                },
-                                _ => {}
-                            }
-                        }
-                    },
-                    Event::Mouse(event) => {
-                        match event.kind { 
-                            MouseEventKind::Down(MouseButton::Left) => {
-                                if event.row < self.num_of_items as u16 +1 {
-                                    self.selected = (event.row-1) as usize + self.start_index;
-                                }
-                            },
-                            MouseEventKind::ScrollUp => {
-                                if self.start_index > 0
-                                && self.end_index > 0 { 
-                                    self.start_index -= 2;
-                                    self.end_index -= 2;
-                                    self.selected = self.start_index;
-                                }
-                            },
-                            MouseEventKind::ScrollDown => {
-                                if self.start_index < self.num_of_items 
-                                && self.end_index + 2 < self.num_of_items
-                                && self.num_of_items > self.height-1 { 
-                                    self.start_index += 2;
-                                    self.end_index += 2;
-                                    self.selected = self.start_index;
-                                }
-                            },
-                            _ => {}
-                        }
-                    },
-                    Event::Resize(_, rows) => {
-                        self.end_index = self.start_index + (rows-1) as usize;
-                    },
-                    _ => {}
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::{FuzzyPicker, Key};
+    ///
+    /// let mut picker = FuzzyPicker::new(&["notes.txt", "todo.md"]);
+    /// picker.bind_item_action(Key::ctrl('d'), |items, selected| {
+    ///     if let Some(index) = selected {
+    ///         items.remove(index);
+    ///     }
+    /// });
+    /// let _ = picker.pick();
+    /// ```
+    pub fn bind_item_action(&mut self, key: Key, action: impl FnMut(&mut Vec<T>, Option<usize>) + 'static) {
+        self.item_actions.insert(key, Box::new(action));
+    }
+
+    /// Binds a key to a command run on the highlighted item outside of the
+    /// picker, e.g. to open it in `$EDITOR`. `pick()` restores the terminal
+    /// for the command's duration and continues picking once it returns,
+    /// so items can be browsed and acted on repeatedly. With
+    /// `handle_event()` the command runs directly, and leaving the
+    /// application's screen is up to it. Takes precedence over `bind()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key that runs the command.
+    /// * `command` - Receives the highlighted item.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use fuzzypicker::{FuzzyPicker, Key};
+    ///
+    /// let mut picker = FuzzyPicker::new(&["notes.txt", "todo.md"]);
+    /// picker.run_external(Key::ctrl('e'), |path| {
+    ///     let _ = Command::new("vi").arg(path).status();
+    /// });
+    /// let _ = picker.pick();
+    /// ```
+    pub fn run_external(&mut self, key: Key, command: impl FnMut(&T) + 'static) {
+        self.external_commands.insert(key, Box::new(command));
+    }
+
+    /// Enables or disables ANSI color code support in item display strings.
+    ///
+    /// When enabled, escape sequences are ignored for matching and layout
+    /// but kept when printing, so pre-colored items (e.g. `git` output)
+    /// render with their own colors. The highlighted item is shown
+    /// without its colors so the selection stays readable.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether display strings may contain ANSI escape sequences.
+    pub fn set_ansi(&mut self, enabled: bool) {
+        self.ansi = enabled;
+    }
+
+    /// Sets how matching items are ordered.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The ranking mode. Defaults to `RankingMode::Score`.
+    pub fn set_ranking_mode(&mut self, mode: RankingMode) {
+        self.ranking_mode = mode;
+    }
+
+    /// Sets how the query is matched against items.
+    ///
+    /// The mode can also be cycled at runtime with `Action::CycleMatchMode`
+    /// (`Ctrl-S` by default).
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The match mode. Defaults to `MatchMode::Fuzzy`.
+    pub fn set_match_mode(&mut self, mode: MatchMode) {
+        self.match_mode = mode;
+    }
+
+    /// Adjusts the score of each matching item before ranking, e.g. to
+    /// boost open buffers or demote archived projects. Only affects
+    /// `RankingMode::Score`.
+    ///
+    /// # Arguments
+    ///
+    /// * `adjust` - Receives an item and its fuzzy score and returns the
+    ///   score to rank it by.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let mut picker = FuzzyPicker::new(&["notes.txt", "archive/notes.txt"]);
+    /// picker.set_score_adjust(|path, score| {
+    ///     if path.starts_with("archive/") { score - 50 } else { score }
+    /// });
+    /// let _ = picker.pick();
+    /// ```
+    pub fn set_score_adjust(&mut self, adjust: impl Fn(&T, i64) -> i64 + 'static) {
+        self.score_adjust = Some(Box::new(adjust));
+    }
+
+    /// Uses `RankingMode::PreserveOrder` for lists with fewer than `threshold` items.
+    ///
+    /// Relevance reordering is confusing in small, menu-like pickers where
+    /// users expect the options to stay put. Defaults to `0` (disabled).
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The item count below which scoring is skipped.
+    pub fn set_preserve_order_threshold(&mut self, threshold: usize) {
+        self.preserve_order_threshold = threshold;
+    }
+
+    /// Lists only the best `max` matches, e.g. for lists with millions of
+    /// items of which only a screenful is ever looked at. The matches are
+    /// then selected before sorting, so only the listed ones are sorted.
+    /// The prompt line still counts all matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The number of matches listed, at least 1.
+    pub fn set_max_results(&mut self, max: usize) {
+        self.max_results = Some(max.max(1));
+    }
+
+    /// Delays matching until the query has not changed for `delay`, so
+    /// typing stays responsive over huge item lists. The query itself is
+    /// echoed immediately. Defaults to `Duration::ZERO` (match on every
+    /// keystroke).
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - How long the query must be unchanged, e.g. 30 ms.
+    pub fn set_debounce(&mut self, delay: Duration) {
+        self.debounce = delay;
+    }
+
+    /// Streams additional items into the picker while it is open.
+    ///
+    /// Items sent on the channel are appended to the list and matched as
+    /// they arrive; streaming ends when every sender has been dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The receiving end of a channel producing items.
+    pub fn set_item_source(&mut self, source: Receiver<T>) {
+        self.item_source = Some(source);
+    }
+
+    /// Groups items under section headers, e.g. "Branches", "Tags" and
+    /// "Remotes". Groups are listed in the order their first item appears
+    /// and are hidden while none of their items match. Headers cannot be
+    /// selected.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Returns the name of the group an item belongs to.
+    pub fn set_group_by(&mut self, group: impl Fn(&T) -> String + 'static) {
+        self.group_by = Some(Box::new(group));
+    }
+
+    /// Shows a short prefix before each item, e.g. an icon or a status
+    /// glyph. Prefixes are not matched against the query and may contain
+    /// ANSI escape sequences, so styled text works:
+    /// `"*".red().to_string()` using crossterm's `Stylize`.
+    ///
+    /// Prefixes are padded to the widest one on screen so items line up.
+    ///
+    /// # Arguments
+    ///
+    /// * `decoration` - Returns the prefix for an item, or an empty string.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let current = "main";
+    /// let mut picker = FuzzyPicker::new(&["main", "feature/foo"]);
+    /// picker.set_decoration(move |branch| {
+    ///     if *branch == current { String::from("*") } else { String::new() }
+    /// });
+    /// let _ = picker.pick();
+    /// ```
+    pub fn set_decoration(&mut self, decoration: impl Fn(&T) -> String + 'static) {
+        self.decoration = Some(Box::new(decoration));
+    }
+
+    /// Numbers the first ten visible items 1–9 and 0, and binds `Alt-1`
+    /// to `Alt-0` to accept them with a single keystroke instead of only
+    /// highlighting them.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to number items. Defaults to `false`.
+    pub fn set_quick_select(&mut self, enabled: bool) {
+        self.quick_select = enabled;
+        for n in 1..=10 {
+            let key = Key::alt(char::from_digit(n as u32 % 10, 10).unwrap_or('0'));
+            if enabled {
+                self.keymap.bind(key, Action::QuickSelect(n));
+            } else if self.keymap.get(&key) == Some(Action::QuickSelect(n)) {
+                match Keymap::default().get(&key) {
+                    Some(action) => self.keymap.bind(key, action),
+                    None => self.keymap.unbind(&key),
                 }
             }
-            self.render_frame()?;	
         }
-        terminal::disable_raw_mode()?;
-        Ok(picked_item)
     }
-    
-    
-    fn filter_by_prompt(&mut self) {
-        self.display_items = self.items.iter()
-            .filter_map(|item| {
-                let display_str = format!("{}", item);
-                if self.prompt.is_empty() || self.matcher.fuzzy_match(
-                    &display_str.to_lowercase(),
-                    &self.prompt.to_lowercase(),
-                ).unwrap_or_default() != 0 {
-                    Some(display_str)
-                } else {
-                    None
-                }
-            })
-            .collect();
 
-        self.display_items.sort_by_key(|item| {
-            -self.matcher.fuzzy_match(
-                &item.to_lowercase(),
-                &self.prompt.to_lowercase(),
-            ).unwrap_or_default()
-        });
-        self.num_of_items = self.display_items.len();
-        self.num_of_displayable_items = self.num_of_items.min(self.height - 1);
-        if self.num_of_displayable_items == 0 {
-            self.end_index = 0;
-        } else {
-            self.end_index = self.num_of_displayable_items - 1;
-        }
+    /// Shows long items on as many rows as they need instead of cutting
+    /// them off at the edge of the terminal.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to wrap long items. Defaults to `false`.
+    pub fn set_line_wrap(&mut self, enabled: bool) {
+        self.line_wrap = enabled;
     }
 
-    fn render_frame(&mut self) -> Result<(), Box<dyn Error>> {
-        // self.debug = format!("noi: {}, sel: {}, si: {}, ei: {}, h:{}", self.num_of_items, self.selected, self.start_index, self.end_index, self.height);
-        let prompt_styled = format!("> {}", self.prompt).green().bold();
-        let debug_info = format!("{}", self.debug).red().bold();
-        
-        self.stdout
-            .queue(Clear(ClearType::All))?
-            .queue(MoveTo(0, 0))?
-            .queue(PrintStyledContent(prompt_styled))?;
-        
-        if !self.debug.is_empty() {
-            self.stdout.queue(MoveTo(20, 0))?
-                .queue(PrintStyledContent(debug_info))?;
-        }
-
-        let mut row = 1;
-        for (index, item) in self.display_items.iter().enumerate().skip(self.start_index).take(self.num_of_displayable_items) {
-            self.stdout
-                .queue(MoveTo(0, row))?
-                .queue(PrintStyledContent(" ".on_dark_grey()))?;
-
-            if index == self.selected {
-                self.stdout
-                    .queue(PrintStyledContent(" ".on_dark_grey()))?
-                    .queue(PrintStyledContent(item.as_str().white().on_dark_grey()))?;
-            } else {
-                self.stdout.queue(Print(format!(" {}", item)))?;
-            }
+    /// Replaces the pointer shown before the highlighted item, by default
+    /// a blank highlighted cell, or `>` without colors. Only its first
+    /// column is shown.
+    ///
+    /// # Arguments
+    ///
+    /// * `pointer` - The pointer, e.g. `"▶"`.
+    pub fn set_pointer(&mut self, pointer: impl Into<String>) {
+        self.indicators.pointer = Some(pointer.into());
+    }
+
+    /// Replaces the `•` shown before items marked in `pick_multi()`.
+    /// Only its first column is shown.
+    ///
+    /// # Arguments
+    ///
+    /// * `marker` - The marker, e.g. `"✓"`.
+    pub fn set_marker(&mut self, marker: impl Into<String>) {
+        self.indicators.marker = Some(marker.into());
+    }
 
-            row += 1;
-        }        
-        self.stdout.queue(MoveTo(self.prompt.len() as u16 + 2, 0))?;
-        self.stdout.flush()?;
-        Ok(())
+    /// Sets whether the whole highlighted row stands out, or only the
+    /// pointer before it.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The highlight mode. Defaults to `HighlightMode::Row`.
+    pub fn set_highlight_mode(&mut self, mode: HighlightMode) {
+        self.indicators.highlight = mode;
+    }
+
+    /// Enables drill-down picking over nested data, e.g. a file browser.
+    ///
+    /// Accepting an item with children lists the children instead of
+    /// returning it; `Backspace` on an empty query goes back up a level.
+    /// The path of parent items is shown before the prompt label.
+    ///
+    /// # Arguments
+    ///
+    /// * `children` - Returns the children of an item, or `None` if it
+    ///   is a leaf that can be picked.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::fs;
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let mut picker = FuzzyPicker::new(&[String::from(".")]);
+    /// picker.set_children(|path| {
+    ///     let entries = fs::read_dir(path).ok()?;
+    ///     Some(entries
+    ///         .filter_map(Result::ok)
+    ///         .map(|entry| entry.path().display().to_string())
+    ///         .collect())
+    /// });
+    /// let _ = picker.pick();
+    /// ```
+    pub fn set_children(&mut self, children: impl FnMut(&T) -> Option<Vec<T>> + 'static) {
+        self.children = Some(Box::new(children));
     }
-}
 
+    /// Sets a callback that regenerates the item list while the picker is
+    /// open, like `fzf --bind 'ctrl-r:reload(...)'`. It runs on
+    /// `Action::Reload` (`Ctrl-R` by default) and at the interval set with
+    /// `set_reload_interval()`. The highlighted item stays selected if it
+    /// is still listed, and with `set_item_id()` marked items stay marked.
+    ///
+    /// # Arguments
+    ///
+    /// * `reload` - Produces the new items.
+    pub fn set_reload(&mut self, reload: impl FnMut() -> Vec<T> + 'static) {
+        self.reload = Some(Box::new(reload));
+    }
+
+    /// Runs the reload callback every `interval` while the picker is open,
+    /// e.g. to keep a list of running processes current.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The time between reloads.
+    pub fn set_reload_interval(&mut self, interval: Duration) {
+        self.reload_interval = Some(interval);
+    }
+
+    /// Identifies items by the value produced by `id`, e.g. a process or
+    /// database id, instead of by their display string. After a reload or
+    /// an item action the highlighted item stays highlighted and marked
+    /// items stay marked if an item with the same id is still listed, even
+    /// if its label changed or another item has the same label.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Produces an item's identity.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::{Entry, FuzzyPicker};
+    ///
+    /// let mut picker = FuzzyPicker::new(&[Entry::new(1, "cargo build"), Entry::new(2, "vim")]);
+    /// picker.set_item_id(|process| process.key);
+    /// let _ = picker.pick_multi();
+    /// ```
+    pub fn set_item_id<K: Hash>(&mut self, id: impl Fn(&T) -> K + 'static) {
+        self.item_id = Some(Box::new(move |item| {
+            let mut hasher = DefaultHasher::new();
+            id(item).hash(&mut hasher);
+            hasher.finish()
+        }));
+    }
+
+    /// Replaces the label shown before the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The prompt label. Defaults to `"> "`.
+    pub fn set_prompt_label(&mut self, label: impl Into<String>) {
+        self.prompt_label = label.into();
+    }
+
+    /// Accepts the only matching item as soon as the query narrows the
+    /// list down to it, or right away if only one item matches when
+    /// picking starts, like fzf's `--select-1`. The item is reported as
+    /// accepted with `Enter`. Streamed items are waited for first.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to accept a single match. Defaults to `false`.
+    pub fn set_auto_accept_single(&mut self, enabled: bool) {
+        self.auto_accept_single = enabled;
+    }
+
+    /// Ends picking with `PickOutcome::Empty` instead of showing an empty
+    /// list when there are no items, like fzf's `--exit-0`. Streamed items
+    /// are waited for first, and a reload that leaves no items also ends
+    /// picking.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to exit when there are no items. Defaults to `false`.
+    pub fn set_exit_on_empty(&mut self, enabled: bool) {
+        self.exit_on_empty = enabled;
+    }
+
+    /// Makes `Enter` accept the query itself while no item matches, e.g. to
+    /// switch to a branch or create it if it doesn't exist. The query is
+    /// returned as `PickOutcome::Query`. Keys bound to
+    /// `Action::AcceptQuery` accept the query regardless of this setting.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::{FuzzyPicker, PickOutcome};
+    ///
+    /// let mut picker = FuzzyPicker::new(&["main", "develop"]);
+    /// picker.set_accept_query(true);
+    /// match picker.pick_outcome() {
+    ///     Ok(PickOutcome::Picked { item, .. }) => println!("git switch {item}"),
+    ///     Ok(PickOutcome::Query { query, .. }) => println!("git switch -c {query}"),
+    ///     _ => {},
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to accept the query. Defaults to `false`.
+    pub fn set_accept_query(&mut self, enabled: bool) {
+        self.accept_query = enabled;
+    }
+
+    /// Switches the accessible mode on or off. It can also be toggled
+    /// while picking with a key bound to `Action::ToggleAccessible`.
+    ///
+    /// In accessible mode the highlighted row is marked with `>` and shown
+    /// in reverse video instead of by color alone, low-contrast gray text
+    /// is avoided, and the loading spinner is not animated, so the screen
+    /// is only redrawn when something changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to use the accessible mode. Defaults to `false`.
+    pub fn set_accessible(&mut self, enabled: bool) {
+        self.accessible = enabled;
+    }
+
+    /// Calls `callback` with the highlighted item whenever the highlight
+    /// moves to another item, e.g. to pass it on to a screen reader.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Receives the newly highlighted item.
+    pub fn set_on_highlight(&mut self, callback: impl FnMut(&T) + 'static) {
+        self.on_highlight = Some(Box::new(callback));
+    }
+
+    /// Keeps the picker open after an item is accepted, passing the item
+    /// to `callback` instead of returning it, so several items can be
+    /// picked one after another without leaving the screen. Picking ends
+    /// when it is cancelled, e.g. with `Esc`.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Receives each accepted item, e.g. to send it on
+    ///   through a channel.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::mpsc;
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let mut picker = FuzzyPicker::new(&["apple", "banana", "cherry"]);
+    /// picker.set_on_accept(move |fruit| {
+    ///     let _ = sender.send(*fruit);
+    /// });
+    /// let _ = picker.pick();
+    /// let basket: Vec<&str> = receiver.try_iter().collect();
+    /// ```
+    pub fn set_on_accept(&mut self, callback: impl FnMut(&T) + 'static) {
+        self.on_accept = Some(Box::new(callback));
+    }
+
+    /// Calls `callback` after the items were matched against a query,
+    /// e.g. on each keystroke, with the time it took and the number of
+    /// items scanned and matched, to profile an application's items.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let mut picker = FuzzyPicker::new(&["apple", "banana"]);
+    /// picker.set_on_match(|metrics| {
+    ///     eprintln!("{:?}: {} of {} in {:?}", metrics.query, metrics.matched, metrics.scanned, metrics.duration);
+    /// });
+    /// let picked = picker.pick();
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Receives the statistics of each finished matching pass.
+    pub fn set_on_match(&mut self, callback: impl FnMut(&MatchMetrics) + 'static) {
+        self.on_match = Some(Box::new(callback));
+    }
+
+    /// Shows a single line of extra information about the item under the
+    /// mouse pointer, e.g. the full path of a truncated entry, next to it.
+    /// The highlight stays where it is, and the tooltip disappears with
+    /// the next key press or click.
+    ///
+    /// # Arguments
+    ///
+    /// * `tooltip` - Produces the tooltip of an item; an empty string shows none.
+    pub fn set_tooltip(&mut self, tooltip: impl Fn(&T) -> String + 'static) {
+        self.tooltip = Some(Box::new(tooltip));
+    }
+
+    /// Returns a handle for updating the prompt label and status text
+    /// from other threads while `pick()` is running.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let mut picker = FuzzyPicker::new(&["a", "b"]);
+    /// let handle = picker.session_handle();
+    /// std::thread::spawn(move || {
+    ///     for percent in (0..=100).step_by(10) {
+    ///         handle.set_status_text(format!("indexing… {percent}%"));
+    ///         std::thread::sleep(std::time::Duration::from_millis(100));
+    ///     }
+    ///     handle.set_status_text("");
+    /// });
+    /// let _ = picker.pick();
+    /// ```
+    pub fn session_handle(&self) -> SessionHandle {
+        self.session.clone()
+    }
+
+    /// Sets the placement of the prompt and the item list.
+    ///
+    /// # Arguments
+    ///
+    /// * `layout` - The screen layout. Defaults to `Layout::Default`.
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+
+    /// Sets the number of lines scrolled by one mouse wheel step.
+    ///
+    /// # Arguments
+    ///
+    /// * `lines` - Lines per wheel step. Defaults to 1.
+    pub fn set_scroll_step(&mut self, lines: usize) {
+        self.scroll_step = lines;
+    }
+
+    /// Keeps rows visible above and below the highlighted item while
+    /// scrolling, like vim's `scrolloff`, so the list scrolls before the
+    /// highlight reaches its edge. Lists too short for the margin keep
+    /// the highlight as close to the middle as they can.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - Rows kept around the highlight. Defaults to 0.
+    pub fn set_scrolloff(&mut self, rows: usize) {
+        self.scrolloff = rows;
+    }
+
+    /// Sets whether moving the highlight past the last item continues at
+    /// the first one and vice versa. Without wrapping the highlight stops
+    /// at either end of the list. Not to be confused with
+    /// `set_line_wrap()`, which wraps long items onto several rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether navigation wraps around. Defaults to `true`.
+    pub fn set_wrap(&mut self, enabled: bool) {
+        self.wrap = enabled;
+    }
+
+    /// Sets the maximum delay between two clicks on the same item for them
+    /// to count as a double-click, which accepts the item.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The double-click interval. Defaults to 500 ms.
+    pub fn set_double_click_interval(&mut self, interval: Duration) {
+        self.double_click_interval = interval;
+    }
+
+    /// Renders the picker inline, in `rows` lines below the cursor, instead
+    /// of taking over the whole screen.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The number of terminal rows to use, including the prompt.
+    pub fn set_height(&mut self, rows: u16) {
+        self.inline_height = Some(rows);
+    }
+
+    /// Pins lines of text, e.g. usage instructions, between the prompt and
+    /// the items. Header lines cannot be matched or selected.
+    ///
+    /// # Arguments
+    ///
+    /// * `lines` - The lines to show, top to bottom.
+    pub fn set_header<S: Into<String>>(&mut self, lines: impl IntoIterator<Item = S>) {
+        self.header = lines.into_iter().map(Into::into).collect();
+    }
+
+    /// Shows a footer line produced by `footer` from the picker's state,
+    /// e.g. contextual key hints. It is redrawn with every frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `footer` - Produces the footer text.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let mut picker = FuzzyPicker::new(&["notes.txt", "todo.md"]);
+    /// picker.set_footer(|info| match info.selected {
+    ///     Some(file) => format!("{}/{}  Enter: open {file}", info.matched, info.total),
+    ///     None => format!("0/{}", info.total),
+    /// });
+    /// let _ = picker.pick();
+    /// ```
+    pub fn set_footer(&mut self, footer: impl Fn(&FooterInfo<T>) -> String + 'static) {
+        self.footer = Some(Box::new(footer));
+    }
+
+    /// Sets the message shown in place of the items when there are none.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message. Defaults to `"no items"`.
+    pub fn set_empty_message(&mut self, message: impl Into<String>) {
+        self.empty_message = message.into();
+    }
+
+    /// Sets the message shown in place of the items when the query
+    /// matches none of them, e.g. `"No results — press Esc to cancel"`.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message. Defaults to `"no matches"`.
+    pub fn set_no_match_message(&mut self, message: impl Into<String>) {
+        self.no_match_message = message.into();
+    }
+
+    /// Displays items as a table, splitting each display string into
+    /// fields at the delimiter set with `set_delimiter()`.
+    ///
+    /// A header row with the column titles is shown above the items.
+    /// The last column holds the rest of the line. Colors are dropped
+    /// from items shown in columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The columns, in display order.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::{Align, Column, FuzzyPicker};
+    ///
+    /// let mut picker = FuzzyPicker::new(&["1 | nginx | web server", "42 | redis | cache"]);
+    /// picker.set_delimiter(" | ");
+    /// picker.set_columns(vec![
+    ///     Column::new("PID").align(Align::Right).searchable(false),
+    ///     Column::new("NAME").width(12),
+    ///     Column::new("DESCRIPTION"),
+    /// ]);
+    /// let _ = picker.pick();
+    /// ```
+    pub fn set_columns(&mut self, columns: Vec<Column>) {
+        self.columns = columns;
+        self.column_focus = None;
+        self.apply_column_defaults();
+    }
+
+    /// Remembers the column widths and hidden columns in the file at
+    /// `path`, so the layout chosen with the column actions is restored
+    /// the next time the picker starts. Use one file per picker.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The layout file. It is written on every accept.
+    pub fn set_column_layout_file(&mut self, path: impl Into<PathBuf>) {
+        self.column_store = Some(ColumnStore::new(path.into()));
+    }
+
+    /// Applies the widths and visibility set by the configuration to the
+    /// columns with the same headers.
+    fn apply_column_defaults(&mut self) {
+        for column in &mut self.columns {
+            let Some(&(width, hidden)) = self.column_defaults.get(column.header()) else {
+                continue;
+            };
+            if let Some(width) = width {
+                column.set_width(width);
+            }
+            if let Some(hidden) = hidden {
+                column.set_hidden(hidden);
+            }
+        }
+    }
+
+    /// Loads the query history and the column layout saved by earlier
+    /// runs, if any. The picker works without them, so unreadable or
+    /// corrupt files are ignored.
+    fn load_saved_state(&mut self) {
+        if let Some(history) = &mut self.history {
+            let _ = history.load();
+        }
+        if let Some(store) = &self.column_store {
+            let _ = store.load(&mut self.columns);
+        }
+    }
+
+    /// Sets the string separating fields within an item's display string.
+    ///
+    /// # Arguments
+    ///
+    /// * `delimiter` - The field separator. Defaults to runs of whitespace.
+    pub fn set_delimiter(&mut self, delimiter: impl Into<String>) {
+        self.delimiter = Some(delimiter.into());
+    }
+
+    /// Restricts matching to some fields of each item, like fzf's `--nth`,
+    /// while the whole line is still displayed. Takes precedence over
+    /// `Column::searchable()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - The fields to match against, split at the delimiter
+    ///   set with `set_delimiter()`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// // Match only the text of `path:line:text` entries.
+    /// let mut picker = FuzzyPicker::new(&["src/lib.rs:12:fn main()"]);
+    /// picker.set_delimiter(":");
+    /// picker.set_nth("3..".parse().unwrap());
+    /// let _ = picker.pick();
+    /// ```
+    pub fn set_nth(&mut self, fields: FieldSpec) {
+        self.nth = Some(fields);
+    }
+
+    /// Matches items against the string produced by `key` instead of their
+    /// display string. Display strings are then only produced for the rows
+    /// that are drawn, which keeps items with expensive `Display`
+    /// implementations, e.g. ones doing lookups, fast to filter. Columns
+    /// and alphabetical ranking still need the display string of every
+    /// item.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Produces the text an item is matched against. Replaces
+    ///   `set_nth()`.
+    pub fn set_match_key(&mut self, key: impl Fn(&T) -> String + 'static) {
+        self.match_key = Some(Box::new(key));
+    }
+
+    /// Lists items with identical display strings only once, e.g. when the
+    /// items are merged from several sources. The first of them is shown
+    /// and returned when picked.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to hide duplicates. Defaults to `false`.
+    pub fn set_dedupe(&mut self, enabled: bool) {
+        self.dedupe = enabled;
+        self.invalidate_item_texts();
+    }
+
+    /// Returns the current query. After `pick()` returns, this is the
+    /// query the user ended with, e.g. to log it or to offer creating a
+    /// new item named after it when nothing was picked.
+    pub fn query(&self) -> &str {
+        &self.prompt
+    }
+
+    /// Returns the items matching the current query, best match first.
+    /// After `pick()` returns, these are the matches for the final query.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let mut picker = FuzzyPicker::new(&["main", "develop"]);
+    /// if let Ok(None) = picker.pick() {
+    ///     if picker.last_matches().is_empty() && !picker.query().is_empty() {
+    ///         println!("create branch {}?", picker.query());
+    ///     }
+    /// }
+    /// ```
+    pub fn last_matches(&self) -> Vec<&T> {
+        self.matches.iter()
+            .filter(|matched| matched.header.is_none())
+            .map(|matched| &self.items[matched.index])
+            .collect()
+    }
+
+    /// Sets the size of the area the picker is drawn in, including the
+    /// prompt line, e.g. after the terminal was resized.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width in columns.
+    /// * `height` - The height in rows.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.width = width as usize;
+        self.height = height as usize;
+        self.relayout();
+    }
+
+    /// Fits the visible window to the rows available for items, e.g. after
+    /// the preview pane was shown or hidden.
+    fn relayout(&mut self) {
+        self.num_of_displayable_items = self.num_of_items.min(self.list_height());
+        self.clamp_viewport();
+    }
+
+    /// Applies a key, mouse, paste or resize event, returning the outcome
+    /// once an item is accepted or the selection is cancelled. Mouse
+    /// positions are those of the terminal, as delivered by crossterm, and
+    /// mouse events outside the picker are ignored. Pasted text is added to
+    /// the query as a single line.
+    pub fn handle_event(&mut self, event: &Event) -> Option<PickOutcome<T>> {
+        self.start();
+        let event = match event {
+            Event::Mouse(event) => Event::Mouse(self.local_mouse_event(event)?),
+            event => event.clone(),
+        };
+        match self.process_event(event) {
+            Handled::Done(outcome) => {
+                self.active = false;
+                Some(outcome)
+            },
+            handled => {
+                if let Handled::RunExternal(key) = handled {
+                    self.run_external_command(&key);
+                }
+                self.update(&mut || false);
+                if self.exits_empty() {
+                    self.active = false;
+                    return Some(PickOutcome::Empty);
+                }
+                if let Handled::Done(outcome) = self.accept_single_match() {
+                    self.active = false;
+                    return Some(outcome);
+                }
+                self.announce_highlight();
+                None
+            },
+        }
+    }
+
+    /// Returns `event` with its column relative to the picker's left edge,
+    /// or `None` if it happened outside the picker.
+    fn local_mouse_event(&self, event: &MouseEvent) -> Option<MouseEvent> {
+        let column = event.column.checked_sub(self.left).filter(|&column| (column as usize) < self.width)?;
+        let rows = self.top..self.top + self.height as u16;
+        rows.contains(&event.row).then_some(MouseEvent { column, ..*event })
+    }
+
+    /// Applies a key press, see [`handle_event`](Self::handle_event).
+    pub fn handle_key(&mut self, event: KeyEvent) -> Option<PickOutcome<T>> {
+        self.handle_event(&Event::Key(event))
+    }
+
+    /// Applies a mouse event, see [`handle_event`](Self::handle_event).
+    pub fn handle_mouse(&mut self, event: MouseEvent) -> Option<PickOutcome<T>> {
+        self.handle_event(&Event::Mouse(event))
+    }
+
+    /// Returns the rows of the item list that fit into the picker's area,
+    /// top to bottom in the default layout.
+    pub fn visible_rows(&mut self) -> Vec<VisibleRow<'_, T>> {
+        self.start();
+        self.update(&mut || false);
+        self.fit_wrapped_viewport();
+        self.matches.iter()
+            .enumerate()
+            .skip(self.start_index)
+            .take(self.num_of_displayable_items)
+            .map(|(position, matched)| match &matched.header {
+                Some(header) => VisibleRow {
+                    item: None,
+                    text: header.clone(),
+                    selected: false,
+                    marked: false,
+                    disabled: false,
+                },
+                None => VisibleRow {
+                    item: Some(&self.items[matched.index]),
+                    text: self.plain_text(&self.display_text(self.item_display(matched.index))).into_owned(),
+                    selected: position == self.selected,
+                    marked: self.marked.contains(&matched.index),
+                    disabled: self.is_disabled(matched.index),
+                },
+            })
+            .collect()
+    }
+
+    /// Matches the items the first time the state is used without `pick()`.
+    fn start(&mut self) {
+        if self.active {
+            return;
+        }
+        self.active = true;
+        self.load_saved_state();
+        self.invalidate_item_texts();
+        self.filter_by_prompt();
+        self.clamp_viewport();
+        self.apply_initial_selection();
+    }
+
+    /// Returns whether the match at `position` can be highlighted.
+    fn selectable(&self, position: usize) -> bool {
+        self.matches.get(position).is_some_and(|matched| {
+            matched.header.is_none() && !self.is_disabled(matched.index)
+        })
+    }
+
+    /// Returns the positions of the visible items numbered by quick-select.
+    fn quick_select_positions(&self) -> Vec<usize> {
+        if !self.quick_select {
+            return Vec::new();
+        }
+        self.visible_positions().take(10).collect()
+    }
+
+    /// Returns the positions of the visible items that can be highlighted,
+    /// from the top of the list.
+    fn visible_positions(&self) -> impl Iterator<Item = usize> + '_ {
+        let visible = self.num_of_displayable_items.min(self.num_of_items.saturating_sub(self.start_index));
+        (self.start_index..self.start_index + visible).filter(|&position| self.selectable(position))
+    }
+
+    /// Highlights the match at `position` if it can be highlighted and is
+    /// not already, scrolling it into view. Returns whether it moved.
+    fn select_position(&mut self, position: Option<usize>) -> bool {
+        match position {
+            Some(position) if position != self.selected => {
+                self.selected = position;
+                self.scroll_to_selected();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Applies one of the marking actions of `pick_multi()`.
+    fn mark(&mut self, action: Action) {
+        let matching: Vec<usize> = (0..self.num_of_items)
+            .filter(|&position| self.selectable(position))
+            .map(|position| self.matches[position].index)
+            .collect();
+        let max = self.max_selections.unwrap_or(usize::MAX);
+        match action {
+            Action::ToggleMark => {
+                let Some(index) = self.selected_match().map(|matched| matched.index) else { return; };
+                if !self.marked.remove(&index) {
+                    if self.marked.len() >= max {
+                        self.selection_limit_hit = true;
+                        return;
+                    }
+                    self.marked.insert(index);
+                }
+                self.next_item();
+            },
+            Action::MarkAll => {
+                for index in matching {
+                    if self.marked.contains(&index) {
+                        continue;
+                    }
+                    if self.marked.len() >= max {
+                        self.selection_limit_hit = true;
+                        break;
+                    }
+                    self.marked.insert(index);
+                }
+            },
+            Action::UnmarkAll => self.marked.clear(),
+            Action::InvertMarks => {
+                let mut marked = self.marked.clone();
+                for index in matching {
+                    if !marked.remove(&index) {
+                        marked.insert(index);
+                    }
+                }
+                if marked.len() > max {
+                    self.selection_limit_hit = true;
+                } else {
+                    self.marked = marked;
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Returns whether the item at `index` is disabled.
+    fn is_disabled(&self, index: usize) -> bool {
+        self.disabled.as_ref().is_some_and(|disabled| disabled(index, &self.items[index]))
+    }
+
+    /// Returns the position of the first match satisfying `predicate`,
+    /// ignoring section headers.
+    fn position_where(&self, predicate: impl Fn(&Match) -> bool) -> Option<usize> {
+        self.matches.iter().position(|matched| matched.header.is_none() && predicate(matched))
+    }
+
+    /// Returns the highlighted match, unless nothing selectable is highlighted.
+    fn selected_match(&self) -> Option<&Match> {
+        self.matches.get(self.selected).filter(|matched| matched.header.is_none())
+    }
+
+    /// Returns whether picking should end because there are no items and
+    /// none are streaming in, with `set_exit_on_empty()` enabled.
+    fn exits_empty(&self) -> bool {
+        self.exit_on_empty && self.items.is_empty() && self.item_source.is_none()
+    }
+
+    /// Accepts the only selectable match if `set_auto_accept_single()` is
+    /// enabled, the matches are up to date and the match was not offered
+    /// before, e.g. when a confirmation for it was declined.
+    fn accept_single_match(&mut self) -> Handled<T> {
+        if !self.auto_accept_single || self.item_source.is_some() || self.pending_filter.is_some() || self.match_job.is_some() {
+            return Handled::Unchanged;
+        }
+        let mut selectable = self.matches.iter()
+            .filter(|matched| matched.header.is_none() && !self.is_disabled(matched.index));
+        let single = match (selectable.next(), selectable.next()) {
+            (Some(matched), None) => Some(matched.index),
+            _ => None,
+        };
+        let offered = std::mem::replace(&mut self.auto_offered, single);
+        let Some(index) = single.filter(|&index| offered != Some(index) && self.confirming.is_none()) else {
+            return Handled::Unchanged;
+        };
+        let Some(position) = self.position_where(|matched| matched.index == index) else {
+            return Handled::Unchanged;
+        };
+        self.selected = position;
+        match self.accept(self.items[index].clone(), Key::plain(KeyCode::Enter)) {
+            Some(outcome) => Handled::Done(outcome),
+            // Drilled down into the item, asking for confirmation or kept open.
+            None => Handled::Redraw,
+        }
+    }
+
+    /// Passes the highlighted item to the `set_on_highlight()` callback if
+    /// it changed since the last call.
+    fn announce_highlight(&mut self) {
+        let index = self.selected_match()
+            .filter(|matched| matched.header.is_none())
+            .map(|matched| matched.index);
+        if index == self.announced {
+            return;
+        }
+        self.announced = index;
+        if let (Some(index), Some(callback)) = (index, &mut self.on_highlight) {
+            callback(&self.items[index]);
+        }
+    }
+
+    fn prev_item(&mut self) {
+        let from = self.selected;
+        for _ in 0..self.num_of_items {
+            if !self.step_prev() {
+                self.selected = from;
+                self.scroll_to_selected();
+                break;
+            }
+            if self.selectable(self.selected) {
+                break;
+            }
+        }
+    }
+
+    fn next_item(&mut self) {
+        let from = self.selected;
+        for _ in 0..self.num_of_items {
+            if !self.step_next() {
+                self.selected = from;
+                self.scroll_to_selected();
+                break;
+            }
+            if self.selectable(self.selected) {
+                break;
+            }
+        }
+    }
+
+    /// Moves the highlight up one row, returning `false` at the first
+    /// row when navigation does not wrap around.
+    fn step_prev(&mut self) -> bool {
+        if self.num_of_items == 0 { return false; }
+        if self.selected > 0 {
+            self.selected -= 1;
+        } else if self.wrap {
+            self.selected = self.num_of_items - 1;
+        } else {
+            return false;
+        }
+        self.scroll_to_selected();
+        true
+    }
+
+    /// Moves the highlight down one row, returning `false` at the last
+    /// row when navigation does not wrap around.
+    fn step_next(&mut self) -> bool {
+        if self.num_of_items == 0 { return false; }
+        if self.selected + 1 < self.num_of_items {
+            self.selected += 1;
+        } else if self.wrap {
+            self.selected = 0;
+        } else {
+            return false;
+        }
+        self.scroll_to_selected();
+        true
+    }
+
+    fn find_next(&mut self) {
+        let Some(word) = self.prompt.split_whitespace().last() else { return; };
+        let word = word.to_lowercase();
+        let found = (1..=self.num_of_items)
+            .map(|offset| (self.selected + offset) % self.num_of_items)
+            .find(|&index| {
+                self.selectable(index)
+                    && self.plain_text(self.text(&self.matches[index])).to_lowercase().contains(&word)
+            });
+        if let Some(index) = found {
+            self.selected = index;
+            self.scroll_to_selected();
+        }
+    }
+
+    /// Completes the query from the matching items, see `Action::Complete`.
+    fn complete(&mut self) {
+        let Some(selected) = self.selected_match() else { return; };
+        let mut texts = self.matches.iter()
+            .filter(|matched| matched.header.is_none())
+            .map(|matched| self.plain_text(self.text(matched)));
+        let first = texts.next().unwrap_or_default().into_owned();
+        let common = texts.fold(first.len(), |len, text| {
+            first[..len].char_indices()
+                .zip(text.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(len.min(text.len()), |((i, _), _)| i)
+        });
+        let common = &first[..common];
+        self.prompt = if common.chars().count() > self.prompt.chars().count() {
+            common.to_string()
+        } else {
+            self.plain_text(self.text(selected)).into_owned()
+        };
+        self.refilter();
+    }
+
+    fn scroll_to_selected(&mut self) {
+        if self.num_of_displayable_items == 0 {
+            return;
+        }
+        let margin = self.scroll_margin();
+        let top = self.selected.saturating_sub(margin);
+        let bottom = (self.selected + margin).min(self.num_of_items.saturating_sub(1));
+        if top < self.start_index {
+            self.start_index = top;
+            self.end_index = self.start_index + self.num_of_displayable_items - 1;
+        } else if bottom > self.end_index {
+            self.end_index = bottom;
+            self.start_index = self.end_index - self.num_of_displayable_items + 1;
+        }
+    }
+
+    /// Returns the rows kept visible around the highlight, see
+    /// `set_scrolloff()`, limited to what fits in the list.
+    fn scroll_margin(&self) -> usize {
+        self.scrolloff.min(self.num_of_displayable_items.saturating_sub(1) / 2)
+    }
+
+    /// Moves the focus of the column actions to the next visible column.
+    /// Returns whether there is one.
+    fn focus_next_column(&mut self) -> bool {
+        let count = self.columns.len();
+        let start = self.column_focus.map_or(0, |focus| focus + 1);
+        let next = (0..count)
+            .map(|offset| (start + offset) % count)
+            .find(|&index| !self.columns[index].is_hidden());
+        self.column_focus = next;
+        next.is_some()
+    }
+
+    /// Returns the focused column, focusing the first visible one if the
+    /// focused column is hidden or none is focused yet.
+    fn focused_column(&mut self) -> Option<usize> {
+        let focus = self.column_focus.filter(|&focus| self.columns.get(focus).is_some_and(|column| !column.is_hidden()));
+        if focus.is_none() {
+            self.column_focus = None;
+            self.focus_next_column();
+        }
+        self.column_focus
+    }
+
+    /// Widens or narrows the focused column. Returns whether its width
+    /// changed.
+    fn resize_column(&mut self, wider: bool) -> bool {
+        let Some(index) = self.focused_column() else {
+            return false;
+        };
+        let column = &mut self.columns[index];
+        let current = column.fixed_width().unwrap_or_else(|| self.column_widths.get(index).copied().unwrap_or_default());
+        let width = if wider {
+            current + COLUMN_RESIZE_STEP
+        } else {
+            current.saturating_sub(COLUMN_RESIZE_STEP).max(1)
+        };
+        column.set_width(width);
+        width != current
+    }
+
+    /// Hides the focused column and focuses the next one. Returns whether
+    /// there was another visible column.
+    fn hide_column(&mut self) -> bool {
+        let Some(index) = self.focused_column() else {
+            return false;
+        };
+        if self.columns.iter().filter(|column| !column.is_hidden()).count() < 2 {
+            return false;
+        }
+        self.columns[index].set_hidden(true);
+        self.focus_next_column()
+    }
+
+    /// Shows the hidden columns. Returns whether there were any.
+    fn show_columns(&mut self) -> bool {
+        let mut shown = false;
+        for column in self.columns.iter_mut().filter(|column| column.is_hidden()) {
+            column.set_hidden(false);
+            shown = true;
+        }
+        shown
+    }
+
+    /// Returns the start and width of the focused column's title.
+    fn focused_column_span(&self) -> Option<(usize, usize)> {
+        columns::span(&self.columns, &self.column_widths, self.column_focus?)
+    }
+
+    fn scroll_left(&mut self) {
+        self.scroll_offset = self.scroll_offset.saturating_sub(HORIZONTAL_SCROLL_STEP);
+    }
+
+    fn scroll_right(&mut self) {
+        let longest = self.matches.iter()
+            .skip(self.start_index)
+            .take(self.num_of_displayable_items)
+            .map(|matched| text::width(&self.plain_text(&self.display_text(self.text(matched)))))
+            .max()
+            .unwrap_or_default();
+        let text_width = self.width.saturating_sub(2);
+        if self.scroll_offset + text_width < longest {
+            self.scroll_offset += HORIZONTAL_SCROLL_STEP;
+        }
+    }
+
+    fn selected_item(&self) -> Option<T> {
+        let matched = self.selected_match()?;
+        if self.is_disabled(matched.index) {
+            return None;
+        }
+        Some(self.items[matched.index].clone())
+    }
+
+    /// Scrolls the list towards its first item, keeping the selection visible.
+    /// Returns whether the list moved.
+    fn scroll_up(&mut self) -> bool {
+        let step = self.scroll_step.min(self.start_index);
+        if step == 0 {
+            return false;
+        }
+        self.start_index -= step;
+        self.end_index -= step;
+        self.selected = self.selected.min(self.end_index - self.scroll_margin());
+        true
+    }
+
+    /// Scrolls the list towards its last item, keeping the selection visible.
+    /// Returns whether the list moved.
+    fn scroll_down(&mut self) -> bool {
+        let last_start = self.num_of_items.saturating_sub(self.num_of_displayable_items);
+        let step = self.scroll_step.min(last_start.saturating_sub(self.start_index));
+        if step == 0 {
+            return false;
+        }
+        self.start_index += step;
+        self.end_index += step;
+        self.selected = self.selected.max(self.start_index + self.scroll_margin());
+        true
+    }
+
+    /// Keeps the selection and the visible window within the matched items.
+    fn clamp_viewport(&mut self) {
+        self.selected = self.selected.min(self.num_of_items.saturating_sub(1));
+        if !self.selectable(self.selected) {
+            let following = (self.selected..self.num_of_items).find(|&position| self.selectable(position));
+            let preceding = (0..self.selected).rev().find(|&position| self.selectable(position));
+            if let Some(position) = following.or(preceding) {
+                self.selected = position;
+            }
+        }
+        if self.num_of_displayable_items == 0 {
+            self.start_index = 0;
+            self.end_index = 0;
+            return;
+        }
+        self.start_index = self.start_index.min(self.num_of_items - self.num_of_displayable_items);
+        self.end_index = self.start_index + self.num_of_displayable_items - 1;
+        self.scroll_to_selected();
+    }
+
+    /// Appends items received from the item source, returning whether any arrived.
+    fn receive_items(&mut self) -> bool {
+        let Some(source) = &self.item_source else { return false; };
+        let mut received = false;
+        let mut disconnected = false;
+        loop {
+            match source.try_recv() {
+                Ok(item) => {
+                    self.items.push(item);
+                    received = true;
+                },
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                }
+            }
+        }
+        if disconnected {
+            self.item_source = None;
+        }
+        if received {
+            self.refilter();
+            self.apply_initial_selection();
+        }
+        received
+    }
+
+    /// Highlights the item requested by `set_initial_selection()`, if it is
+    /// among the matches, and forgets the request once it is satisfied.
+    fn apply_initial_selection(&mut self) {
+        if let Some(position) = self.initial_selection_position() {
+            self.selected = position;
+            self.scroll_to_selected();
+            self.initial_selection = None;
+        }
+    }
+
+    /// Returns the position among the matches of the item requested by
+    /// `set_initial_selection()`.
+    fn initial_selection_position(&self) -> Option<usize> {
+        let predicate = self.initial_selection.as_ref()?;
+        self.position_where(|matched| predicate(matched.index, &self.items[matched.index]))
+    }
+
+    /// Applies updates made through session handles, returning whether any were pending.
+    fn apply_session_updates(&mut self) -> bool {
+        let updates = self.session.take_updates();
+        let mut changed = false;
+        if let Some(label) = updates.prompt_label {
+            self.prompt_label = label;
+            changed = true;
+        }
+        if let Some(text) = updates.status_text {
+            self.status_text = text;
+            changed = true;
+        }
+        changed
+    }
+
+    /// Replaces the items with those from the reload callback, keeping the
+    /// highlighted item selected by its display string. Returns whether a
+    /// callback is set.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn reload_items(&mut self) -> bool {
+        let Some(reload) = &mut self.reload else { return false; };
+        let items = reload();
+        let selected_text = self.selected_match().map(|matched| self.text(matched).to_string());
+        let tracked = self.tracked_items();
+        self.items = items;
+        self.invalidate_item_texts();
+        self.marked.clear();
+        self.last_reload = Instant::now();
+        self.filter_by_prompt();
+        if let Some(tracked) = tracked {
+            self.restore_tracked_items(tracked);
+        } else if let Some(position) = selected_text.and_then(|text| {
+            self.position_where(|matched| self.text(matched) == text)
+        }) {
+            self.selected = position;
+        }
+        self.clamp_viewport();
+        true
+    }
+
+    /// Returns the ids of the highlighted and marked items, if items are
+    /// identified with `set_item_id()`.
+    fn tracked_items(&self) -> Option<TrackedItems> {
+        let id = self.item_id.as_ref()?;
+        Some(TrackedItems {
+            highlighted: self.selected_match().map(|matched| id(&self.items[matched.index])),
+            marked: self.marked.iter().map(|&index| id(&self.items[index])).collect(),
+        })
+    }
+
+    /// Highlights and marks the items with the ids in `tracked` again
+    /// after the item list was replaced.
+    fn restore_tracked_items(&mut self, tracked: TrackedItems) {
+        let Some(id) = &self.item_id else { return; };
+        if !tracked.marked.is_empty() {
+            self.marked = self.items.iter()
+                .enumerate()
+                .filter(|(_, item)| tracked.marked.contains(&id(item)))
+                .map(|(index, _)| index)
+                .collect();
+        }
+        let position = tracked.highlighted.and_then(|highlighted| {
+            self.position_where(|matched| id(&self.items[matched.index]) == highlighted)
+        });
+        if let Some(position) = position {
+            self.selected = position;
+            self.clamp_viewport();
+        }
+    }
+
+    /// Lists the children of `item` if it has any, returning whether it did.
+    fn descend(&mut self, item: &T) -> bool {
+        let Some(children) = &mut self.children else { return false; };
+        let Some(children) = children(item) else { return false; };
+        let Some(index) = self.selected_match().map(|matched| matched.index) else { return false; };
+        self.levels.push(Level {
+            items: std::mem::replace(&mut self.items, children),
+            query: std::mem::take(&mut self.prompt),
+            parent: item.clone(),
+            index,
+        });
+        self.invalidate_item_texts();
+        self.marked.clear();
+        self.filter_by_prompt();
+        self.reset_scroll();
+        self.clamp_viewport();
+        true
+    }
+
+    /// Returns to the items containing the current level, highlighting
+    /// the item that was descended into.
+    fn ascend(&mut self) {
+        let Some(level) = self.levels.pop() else { return; };
+        self.items = level.items;
+        self.prompt = level.query;
+        self.invalidate_item_texts();
+        self.marked.clear();
+        self.filter_by_prompt();
+        match self.position_where(|matched| matched.index == level.index) {
+            Some(position) => self.selected = position,
+            None => self.reset_scroll(),
+        }
+        self.clamp_viewport();
+    }
+
+    /// Returns the text before the query: the path of parent items while
+    /// drilling down, then the prompt label.
+    fn prompt_prefix(&self) -> String {
+        let mut prefix = String::new();
+        for level in &self.levels {
+            prefix.push_str(&self.plain_text(&level.parent.to_string()));
+            prefix.push_str(" / ");
+        }
+        prefix.push_str(&self.prompt_label);
+        prefix.push_str(&self.chips_text());
+        prefix
+    }
+
+    /// Returns the time left until the next automatic reload, if any.
+    fn time_until_reload(&self) -> Option<Duration> {
+        self.reload.as_ref()?;
+        let interval = self.reload_interval?;
+        Some(interval.saturating_sub(self.last_reload.elapsed()))
+    }
+
+    fn history_prev(&mut self) {
+        let Some(history) = &mut self.history else { return; };
+        if let Some(query) = history.prev(&self.prompt) {
+            self.prompt = query.to_string();
+            self.refilter();
+        }
+    }
+
+    fn history_next(&mut self) {
+        let Some(history) = &mut self.history else { return; };
+        if let Some(query) = history.next() {
+            self.prompt = query.to_string();
+            self.refilter();
+        }
+    }
+
+    /// Re-matches the items, keeping the highlighted item selected if it
+    /// still matches and falling back to the top of the list otherwise.
+    fn refilter(&mut self) {
+        self.try_refilter(&mut || false);
+    }
+
+    /// Like `refilter()`, but gives up and keeps the previous matches as
+    /// soon as `input_pending` reports waiting input. Returns whether it
+    /// finished.
+    fn try_refilter(&mut self, input_pending: &mut dyn FnMut() -> bool) -> bool {
+        let selected_index = self.selected_match().map(|matched| matched.index);
+        if !self.try_filter_by_prompt(input_pending) {
+            return false;
+        }
+        self.pending_filter = None;
+        self.reselect(selected_index);
+        true
+    }
+
+    /// Highlights the item at `index` after re-matching if it still
+    /// matches, and the top of the list otherwise.
+    fn reselect(&mut self, index: Option<usize>) {
+        let position = index.and_then(|index| {
+            self.position_where(|matched| matched.index == index)
+        });
+        match position {
+            Some(position) => {
+                self.selected = position;
+                self.clamp_viewport();
+            },
+            None => self.reset_scroll(),
+        }
+    }
+
+    /// Schedules a re-match after the query was typed into. It runs once
+    /// the debounce delay has passed and is abandoned while more input is
+    /// waiting, so stale matches never hold up typing.
+    fn query_edited(&mut self) {
+        self.pending_filter = Some(Instant::now());
+    }
+
+    /// Runs the command bound to `key` with `run_external()` on the
+    /// highlighted item. The item may have changed, so its preview is
+    /// produced again.
+    fn run_external_command(&mut self, key: &Key) {
+        let Some(index) = self.selected_match().map(|matched| matched.index) else { return; };
+        if let Some(command) = self.external_commands.get_mut(key) {
+            command(&self.items[index]);
+        }
+        self.stop_preview();
+    }
+
+    /// Runs a re-match delayed by the debounce or still running in the
+    /// background, if any.
+    fn apply_pending_filter(&mut self) {
+        if self.pending_filter.is_some() || self.match_job.is_some() {
+            self.refilter();
+        }
+    }
+
+    /// Returns whether `event` only types into or deletes from the query,
+    /// which may leave a debounced re-match pending.
+    fn edits_query(&self, event: &Event) -> bool {
+        let event = match event {
+            Event::Key(event) => event,
+            Event::Paste(_) => return true,
+            _ => return false,
+        };
+        let key = Key::from(*event);
+        match self.keymap.get(&key) {
+            None => key.typed_char().is_some(),
+            Some(Action::CancelOnEmptyQuery) => !self.prompt.is_empty() && key.typed_char().is_some(),
+            Some(Action::DeleteChar | Action::DeleteWord) => !self.prompt.is_empty(),
+            Some(_) => false,
+        }
+    }
+
+    fn reset_scroll(&mut self) {
+        self.start_index = 0;
+        self.selected = (0..self.num_of_items)
+            .find(|&position| self.selectable(position))
+            .unwrap_or_default();
+    }
+    
+    /// Limits how many items can be marked in `pick_multi()`, e.g. to pick
+    /// exactly two refs to diff. Marking more is refused and the count is
+    /// shown in red.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum number of marked items.
+    pub fn set_max_selections(&mut self, max: usize) {
+        self.max_selections = Some(max);
+    }
+
+    /// Applies streamed items, session updates, due reloads, debounced
+    /// matching, which `input_pending` can interrupt unless it runs in the
+    /// background, and preview output. Returns whether anything changed.
+    fn update(&mut self, input_pending: &mut dyn FnMut() -> bool) -> bool {
+        let mut changed = self.receive_items();
+        changed |= self.apply_session_updates();
+        if self.time_until_reload() == Some(Duration::ZERO) {
+            changed |= self.reload_items();
+        }
+        if self.pending_filter.is_some_and(|edited| edited.elapsed() >= self.debounce) {
+            changed |= if self.background_matching {
+                self.start_background_match()
+            } else {
+                self.try_refilter(input_pending)
+            };
+        }
+        changed |= self.finish_background_match();
+        changed |= self.update_preview();
+        changed
+    }
+
+    /// Applies a key, mouse or resize event to the picker.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    fn process_event(&mut self, event: Event) -> Handled<T> {
+        let hovering = matches!(&event, Event::Mouse(mouse) if mouse.kind == MouseEventKind::Moved);
+        if !hovering && self.hovered.take().is_some() {
+            // Any other input hides the tooltip.
+            return match self.process_event(event) {
+                Handled::Unchanged => Handled::Redraw,
+                handled => handled,
+            };
+        }
+        // Once the user interacts, streamed items must not move the highlight.
+        self.initial_selection = None;
+        // Anything but further typing acts on the matches for the current query.
+        if !self.edits_query(&event) {
+            self.apply_pending_filter();
+        }
+        match event {
+            Event::Key(event) if event.kind == KeyEventKind::Press => {
+                let key = Key::from(event);
+                self.selection_limit_hit = false;
+                if let Some((item, accept_key)) = self.confirming.take() {
+                    return match key.code {
+                        KeyCode::Char('y' | 'Y') => match self.finish(item, accept_key) {
+                            Some(outcome) => Handled::Done(outcome),
+                            None => Handled::Redraw,
+                        },
+                        KeyCode::Char('n' | 'N') | KeyCode::Esc => Handled::Redraw,
+                        _ => {
+                            self.confirming = Some((item, accept_key));
+                            Handled::Unchanged
+                        },
+                    };
+                }
+                if self.help_visible {
+                    // Any key closes the help without acting on the items.
+                    self.help_visible = false;
+                    return Handled::Redraw;
+                }
+                let selected = self.selected_match().map(|matched| matched.index);
+                if self.item_actions.contains_key(&key) {
+                    let tracked = self.tracked_items();
+                    if let Some(action) = self.item_actions.get_mut(&key) {
+                        action(&mut self.items, selected);
+                    }
+                    // Marks refer to positions in the item list, which the action may have changed.
+                    self.marked.clear();
+                    self.invalidate_item_texts();
+                    self.refilter();
+                    if let Some(tracked) = tracked {
+                        self.restore_tracked_items(tracked);
+                    }
+                    return Handled::Redraw;
+                }
+                if self.external_commands.contains_key(&key) {
+                    return match selected {
+                        Some(_) => Handled::RunExternal(key),
+                        None => Handled::Unchanged,
+                    };
+                }
+                match self.keymap.get(&key) {
+                    Some(Action::Accept) => {
+                        // With nothing matching there is nothing to accept; keep
+                        // the picker open so the query can be corrected.
+                        let Some(item) = self.selected_item() else {
+                            if self.accept_query && self.matches.is_empty() && !self.prompt.is_empty() {
+                                return Handled::Done(self.finish_query(key));
+                            }
+                            return Handled::Unchanged;
+                        };
+                        if let Some(outcome) = self.accept(item, key) {
+                            return Handled::Done(outcome);
+                        }
+                    },
+                    Some(Action::QuickSelect(n)) => {
+                        let Some(&position) = self.quick_select_positions().get(n.wrapping_sub(1)) else {
+                            return Handled::Unchanged;
+                        };
+                        self.selected = position;
+                        let accepted = self.selected_item().and_then(|item| self.accept(item, key));
+                        if let Some(outcome) = accepted {
+                            return Handled::Done(outcome);
+                        }
+                    },
+                    Some(Action::JumpTo(n)) => {
+                        let position = self.visible_positions().nth(n.wrapping_sub(1));
+                        if !self.select_position(position) {
+                            return Handled::Unchanged;
+                        }
+                    },
+                    Some(Action::SelectFirst) => {
+                        let position = (0..self.num_of_items).find(|&position| self.selectable(position));
+                        if !self.select_position(position) {
+                            return Handled::Unchanged;
+                        }
+                    },
+                    Some(Action::SelectLast) => {
+                        let position = (0..self.num_of_items).rev().find(|&position| self.selectable(position));
+                        if !self.select_position(position) {
+                            return Handled::Unchanged;
+                        }
+                    },
+                    Some(Action::AcceptQuery) if self.prompt.is_empty() => return Handled::Unchanged,
+                    Some(Action::AcceptQuery) => return Handled::Done(self.finish_query(key)),
+                    Some(Action::Cancel) => return Handled::Done(PickOutcome::Cancelled { key }),
+                    Some(action @ (Action::ToggleMark | Action::MarkAll | Action::UnmarkAll | Action::InvertMarks)) => {
+                        if !self.multi_select {
+                            return Handled::Unchanged;
+                        }
+                        self.mark(action);
+                    },
+                    Some(Action::CancelOnEmptyQuery) if self.prompt.is_empty() => {
+                        return Handled::Done(PickOutcome::Cancelled { key });
+                    },
+                    Some(Action::CancelOnEmptyQuery) => match key.typed_char() {
+                        Some(ch) => {
+                            self.prompt.push(ch);
+                            self.query_edited();
+                        },
+                        None => return Handled::Unchanged
+                    },
+                    Some(Action::SelectPrev) if self.num_of_items == 0 => self.history_prev(),
+                    Some(Action::SelectNext) if self.num_of_items == 0 => self.history_next(),
+                    Some(Action::SelectPrev) => self.prev_item(),
+                    Some(Action::SelectNext) => self.next_item(),
+                    Some(Action::HistoryPrev) => self.history_prev(),
+                    Some(Action::HistoryNext) => self.history_next(),
+                    Some(Action::CycleMatchMode) => {
+                        self.match_mode = self.match_mode.next();
+                        self.refilter();
+                    },
+                    Some(Action::DeleteChar) if self.prompt.is_empty() && !self.chips.is_empty() => {
+                        self.remove_chip(self.chips.len() - 1);
+                    },
+                    Some(Action::DeleteChar) if self.prompt.is_empty() => self.ascend(),
+                    Some(Action::DeleteChar) => {
+                        text::pop_grapheme(&mut self.prompt);
+                        self.query_edited();
+                    },
+                    Some(Action::DeleteWord) if self.prompt.is_empty() => return Handled::Unchanged,
+                    Some(Action::DeleteWord) => {
+                        let word_start = self.prompt
+                            .trim_end()
+                            .trim_end_matches(|ch: char| !ch.is_whitespace())
+                            .len();
+                        self.prompt.truncate(word_start);
+                        self.query_edited();
+                    },
+                    Some(Action::Reload) if !self.reload_items() => return Handled::Unchanged,
+                    Some(Action::Reload) => {},
+                    Some(Action::PromoteQuery) if !self.promote_query() => return Handled::Unchanged,
+                    Some(Action::PromoteQuery) => {},
+                    Some(Action::Narrow) if !self.narrow() => return Handled::Unchanged,
+                    Some(Action::Widen) if !self.widen() => return Handled::Unchanged,
+                    Some(Action::Narrow | Action::Widen) => {},
+                    Some(Action::Complete) => self.complete(),
+                    Some(Action::FindNext) => self.find_next(),
+                    Some(Action::ScrollLeft) => self.scroll_left(),
+                    Some(Action::ScrollRight) => self.scroll_right(),
+                    Some(Action::NextColumn) if !self.focus_next_column() => return Handled::Unchanged,
+                    Some(Action::WidenColumn) if !self.resize_column(true) => return Handled::Unchanged,
+                    Some(Action::NarrowColumn) if !self.resize_column(false) => return Handled::Unchanged,
+                    Some(Action::HideColumn) if !self.hide_column() => return Handled::Unchanged,
+                    Some(Action::ShowColumns) if !self.show_columns() => return Handled::Unchanged,
+                    Some(Action::NextColumn | Action::WidenColumn | Action::NarrowColumn | Action::HideColumn | Action::ShowColumns) => {},
+                    Some(Action::ToggleAccessible) => self.accessible = !self.accessible,
+                    Some(Action::ToggleHelp) => self.help_visible = true,
+                    Some(Action::PreviewUp) if !self.scroll_preview(true, 1) => return Handled::Unchanged,
+                    Some(Action::PreviewDown) if !self.scroll_preview(false, 1) => return Handled::Unchanged,
+                    Some(Action::PreviewUp | Action::PreviewDown) => {},
+                    Some(Action::Suspend) => return Handled::Suspend,
+                    Some(Action::TogglePreview) if self.preview.is_none() => return Handled::Unchanged,
+                    Some(Action::TogglePreview) => {
+                        self.preview_hidden = !self.preview_hidden;
+                        self.relayout();
+                    },
+                    None => match key.typed_char() {
+                        Some(ch) => {
+                            self.prompt.push(ch);
+                            self.query_edited();
+                        },
+                        None => return Handled::Unchanged
+                    }
+                }
+            },
+            Event::Paste(_) if self.confirming.is_some() => return Handled::Unchanged,
+            Event::Paste(text) => {
+                let text = paste_text(&text);
+                if text.is_empty() {
+                    return Handled::Unchanged;
+                }
+                self.selection_limit_hit = false;
+                self.help_visible = false;
+                self.prompt.push_str(&text);
+                self.query_edited();
+            },
+            Event::Mouse(_) if self.confirming.is_some() => return Handled::Unchanged,
+            Event::Mouse(event) if hovering => return self.hover(event.column, event.row),
+            Event::Mouse(event) if self.in_preview(event.column, event.row) => {
+                let scrolled = match event.kind {
+                    MouseEventKind::ScrollUp => self.scroll_preview(true, self.scroll_step),
+                    MouseEventKind::ScrollDown => self.scroll_preview(false, self.scroll_step),
+                    _ => false,
+                };
+                if !scrolled {
+                    return Handled::Unchanged;
+                }
+            },
+            Event::Mouse(event) => {
+                match event.kind { 
+                    MouseEventKind::Down(MouseButton::Left) if event.row == self.prompt_row() => {
+                        let Some(position) = self.chip_at_column(event.column) else {
+                            return Handled::Unchanged;
+                        };
+                        self.remove_chip(position);
+                    },
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        let clicked = self.index_at_row(event.row)
+                            .filter(|&index| self.selectable(index));
+                        let Some(index) = clicked else {
+                            return Handled::Unchanged;
+                        };
+                        let now = Instant::now();
+                        let double_click = self.last_click.is_some_and(|(time, clicked)| {
+                            clicked == index
+                                && now.duration_since(time) <= self.double_click_interval
+                        });
+                        self.selected = index;
+                        if double_click {
+                            let accepted = self.selected_item()
+                                .and_then(|item| self.accept(item, Key::plain(KeyCode::Enter)));
+                            if let Some(outcome) = accepted {
+                                return Handled::Done(outcome);
+                            }
+                        }
+                        self.last_click = Some((now, index));
+                    },
+                    MouseEventKind::ScrollUp => {
+                        let scrolled = match self.layout {
+                            Layout::Default => self.scroll_up(),
+                            Layout::Reverse => self.scroll_down(),
+                        };
+                        if !scrolled {
+                            return Handled::Unchanged;
+                        }
+                    },
+                    MouseEventKind::ScrollDown => {
+                        let scrolled = match self.layout {
+                            Layout::Default => self.scroll_down(),
+                            Layout::Reverse => self.scroll_up(),
+                        };
+                        if !scrolled {
+                            return Handled::Unchanged;
+                        }
+                    },
+                    _ => return Handled::Unchanged
+                }
+            },
+            Event::Resize(columns, rows) => {
+                // Inline pickers take the rows they asked for, as far as the terminal has them.
+                let height = match self.inline_height {
+                    Some(requested) => {
+                        let height = requested.clamp(1, rows.max(1));
+                        // Move the picker up if it no longer fits below its top row.
+                        self.top = self.top.min(rows.saturating_sub(height));
+                        height
+                    },
+                    None => rows,
+                };
+                self.resize(columns, height);
+            },
+            _ => return Handled::Unchanged
+        }
+        Handled::Redraw
+    }
+
+    /// Shows the tooltip of the item under the mouse pointer at `column`
+    /// and `row`, if any.
+    fn hover(&mut self, column: u16, row: u16) -> Handled<T> {
+        if self.tooltip.is_none() {
+            return Handled::Unchanged;
+        }
+        let hovered = self.index_at_row(row)
+            .filter(|_| (column as usize) < self.list_width())
+            .and_then(|position| self.matches.get(position))
+            .filter(|matched| matched.header.is_none())
+            .map(|matched| (matched.index, row));
+        if hovered == self.hovered {
+            return Handled::Unchanged;
+        }
+        self.hovered = hovered;
+        Handled::Redraw
+    }
+
+    /// Returns the tooltip of the hovered item and the row to show it on:
+    /// below the item, or above it at the end of the list.
+    fn tooltip_line(&self) -> Option<(u16, String)> {
+        let (index, row) = self.hovered?;
+        let text = (self.tooltip.as_ref()?)(self.items.get(index)?);
+        let text = ansi::strip(text.lines().next()?).into_owned();
+        if text.is_empty() {
+            return None;
+        }
+        let last_offset = self.list_height().checked_sub(1)?;
+        let (first, last) = (self.item_row(0), self.item_row(last_offset));
+        let (first, last) = (first.min(last), first.max(last));
+        let row = if row < last {
+            row + 1
+        } else {
+            row.checked_sub(1).filter(|&above| above >= first)?
+        };
+        Some((row, text))
+    }
+
+    /// Handles an accepted item: descends into its children, asks for
+    /// confirmation, or finishes picking, see [`finish`](Self::finish).
+    /// Returns the outcome once the pick is final.
+    fn accept(&mut self, item: T, key: Key) -> Option<PickOutcome<T>> {
+        if self.descend(&item) {
+            return None;
+        }
+        let index = self.selected_match().map_or(0, |matched| matched.index);
+        if self.confirmation.as_ref().is_some_and(|confirmation| confirmation(index, &item)) {
+            self.confirming = Some((item, key));
+            return None;
+        }
+        self.finish(item, key)
+    }
+
+    /// Records the query in the history, saves the column layout and
+    /// returns the final outcome, or passes the item to the
+    /// `set_on_accept()` callback and keeps picking.
+    fn finish(&mut self, item: T, key: Key) -> Option<PickOutcome<T>> {
+        self.save_state();
+        match &mut self.on_accept {
+            Some(callback) => {
+                callback(&item);
+                None
+            },
+            None => Some(PickOutcome::Picked { item, key }),
+        }
+    }
+
+    /// Records the query in the history, saves the column layout and
+    /// returns the query as the outcome.
+    fn finish_query(&mut self, key: Key) -> PickOutcome<T> {
+        self.save_state();
+        PickOutcome::Query { query: self.prompt.clone(), key }
+    }
+
+    fn save_state(&mut self) {
+        if let Some(history) = &mut self.history {
+            // Failing to save the query must not discard the pick.
+            let _ = history.record(&self.prompt);
+        }
+        if let Some(store) = &self.column_store {
+            let _ = store.save(&self.columns);
+        }
+    }
+
+    
+    fn filter_by_prompt(&mut self) {
+        self.try_filter_by_prompt(&mut || false);
+    }
+
+    /// Matches the items against the query, checking `input_pending`
+    /// between chunks of items and keeping the previous matches if input is
+    /// waiting. Returns whether matching finished.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(query = %self.prompt)))]
+    fn try_filter_by_prompt(&mut self, input_pending: &mut dyn FnMut() -> bool) -> bool {
+        let Some((pattern, job)) = self.prepare_match() else {
+            return true;
+        };
+        let preserve_order = job.ranking_mode == RankingMode::PreserveOrder;
+        let texts: Vec<&str> = job.candidates.iter()
+            .map(|&index| &*self.item_texts[index].searchable)
+            .collect();
+        let mut scores = Vec::with_capacity(texts.len());
+        for chunk in texts.chunks(MATCH_CHUNK_SIZE) {
+            if !scores.is_empty() && input_pending() {
+                #[cfg(feature = "tracing")]
+                tracing::trace!("matching interrupted by input");
+                return false;
+            }
+            scores.extend(pattern.score_all(&self.matcher, chunk, preserve_order));
+        }
+        self.finish_match(job, scores);
+        true
+    }
+
+    /// Prepares matching the items against the query, abandoning a match
+    /// running in the background, and collects the items left to score.
+    /// Returns `None`, keeping the previous matches, if the query is not a
+    /// valid pattern.
+    fn prepare_match(&mut self) -> Option<(Pattern, MatchJob)> {
+        if self.match_job.take().is_some() {
+            self.ranker.cancel();
+        }
+        let pattern = match Pattern::new(&self.prompt, self.match_mode) {
+            Ok(pattern) => pattern,
+            Err(error) => {
+                // Keep showing the previous matches until the pattern is valid again.
+                self.query_error = Some(error);
+                return None;
+            }
+        };
+        self.query_error = None;
+        let started = Instant::now();
+        let ranking_mode = if self.items.len() < self.preserve_order_threshold {
+            RankingMode::PreserveOrder
+        } else {
+            self.ranking_mode
+        };
+        let preserve_order = ranking_mode == RankingMode::PreserveOrder;
+        let query = self.prompt.to_lowercase();
+        let num_of_items = self.items.len();
+        self.cache_item_texts(num_of_items);
+        let mut candidates = match self.match_cache.candidates(&query, self.match_mode, preserve_order) {
+            Some((mut cached, checked)) => {
+                // Items received since the cached matches still need narrowing and the chips applied.
+                cached.extend(self.base_matches(checked..num_of_items));
+                cached
+            },
+            None => self.base_matches(0..num_of_items),
+        };
+        if self.dedupe {
+            candidates.retain(|&index| !self.item_texts[index].duplicate);
+        }
+        Some((pattern, MatchJob { query, ranking_mode, num_of_items, candidates, started }))
+    }
+
+    /// Ranks the candidates of `job` that have a score, `scores` being in
+    /// the same order, and makes them the matches.
+    fn finish_match(&mut self, job: MatchJob, scores: Vec<Option<i64>>) {
+        let MatchJob { query, ranking_mode, num_of_items, candidates, started } = job;
+        let preserve_order = ranking_mode == RankingMode::PreserveOrder;
+        let scanned = candidates.len();
+        let mut matches: Vec<(i64, Match)> = candidates.into_iter()
+            .zip(scores)
+            .filter_map(|(index, score)| {
+                let score = match &self.score_adjust {
+                    Some(adjust) if !preserve_order => adjust(&self.items[index], score?),
+                    _ => score?,
+                };
+                Some((score, Match { index, header: None }))
+            })
+            .collect();
+        let matched = matches.iter().map(|(_, matched)| matched.index).collect();
+        self.match_cache.insert(&query, self.match_mode, preserve_order, num_of_items, matched);
+
+        // Ties are broken by original index so equal items keep their
+        // relative order between keystrokes.
+        let by_score = |(a_score, a): &(i64, Match), (b_score, b): &(i64, Match)| {
+            b_score.cmp(a_score).then(a.index.cmp(&b.index))
+        };
+        let by_name = |(_, matched): &(i64, Match)| {
+            (self.plain_text(self.text(matched)).to_lowercase(), matched.index)
+        };
+        let num_of_matches = matches.len();
+        if let Some(max) = self.max_results.filter(|&max| max < num_of_matches) {
+            // Move the best matches to the front without sorting the rest.
+            match ranking_mode {
+                RankingMode::Score => {
+                    matches.select_nth_unstable_by(max - 1, by_score);
+                },
+                RankingMode::Alphabetical => {
+                    matches.select_nth_unstable_by_key(max - 1, by_name);
+                },
+                RankingMode::PreserveOrder | RankingMode::Unsorted => {},
+            }
+            matches.truncate(max);
+        }
+        match ranking_mode {
+            RankingMode::Score => matches.sort_by(by_score),
+            RankingMode::Alphabetical => matches.sort_by_cached_key(by_name),
+            RankingMode::PreserveOrder | RankingMode::Unsorted => {},
+        }
+        let matches: Vec<Match> = matches.into_iter()
+            .map(|(_, matched)| matched)
+            .collect();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(scanned, matched = num_of_matches, duration = ?started.elapsed(), "matched");
+        if let Some(callback) = &mut self.on_match {
+            callback(&MatchMetrics {
+                query: &self.prompt,
+                duration: started.elapsed(),
+                scanned,
+                matched: num_of_matches,
+            });
+        }
+        self.set_matches(matches);
+        self.num_of_matches = num_of_matches;
+        self.num_of_displayable_items = self.num_of_items.min(self.list_height());
+        if self.num_of_displayable_items == 0 {
+            self.end_index = 0;
+        } else {
+            self.end_index = self.num_of_displayable_items - 1;
+        }
+    }
+
+    /// Starts matching the items against the edited query on a background
+    /// thread. Returns whether the query turned out invalid, which is shown
+    /// instead.
+    fn start_background_match(&mut self) -> bool {
+        self.pending_filter = None;
+        let Some((pattern, job)) = self.prepare_match() else {
+            return true;
+        };
+        let texts = job.candidates.iter()
+            .map(|&index| Arc::clone(&self.item_texts[index].searchable))
+            .collect();
+        self.ranker.start(pattern, texts, job.ranking_mode == RankingMode::PreserveOrder);
+        self.match_job = Some(job);
+        false
+    }
+
+    /// Makes the results of the background match the matches once it has
+    /// finished, keeping the highlighted item selected or highlighting the
+    /// one requested by `set_initial_selection()`. Returns whether it
+    /// finished.
+    fn finish_background_match(&mut self) -> bool {
+        if self.match_job.is_none() {
+            return false;
+        }
+        let Some(scores) = self.ranker.finished() else {
+            return false;
+        };
+        let selected_index = self.selected_match().map(|matched| matched.index);
+        if let Some(job) = self.match_job.take() {
+            self.finish_match(job, scores);
+        }
+        self.reselect(selected_index);
+        self.apply_initial_selection();
+        true
+    }
+
+    /// Returns the indices in `range` of the items that are in the
+    /// narrowed item list, if any, and match every filter chip. Items
+    /// received after narrowing are never in the narrowed list.
+    fn base_matches(&self, range: Range<usize>) -> Vec<usize> {
+        let candidates: Vec<usize> = match &self.narrowed {
+            Some(narrowed) => narrowed.iter().copied().filter(|index| range.contains(index)).collect(),
+            None => range.collect(),
+        };
+        self.chip_matches(candidates)
+    }
+
+    /// Returns the `candidates` that match every filter chip.
+    fn chip_matches(&self, mut candidates: Vec<usize>) -> Vec<usize> {
+        for (query, mode) in &self.chips {
+            let Ok(pattern) = Pattern::new(query, *mode) else { continue; };
+            let texts: Vec<&str> = candidates.iter()
+                .map(|&index| &*self.item_texts[index].searchable)
+                .collect();
+            let scores = pattern.score_all(&self.matcher, &texts, false);
+            candidates = candidates.into_iter()
+                .zip(scores)
+                .filter_map(|(index, score)| score.map(|_| index))
+                .collect();
+        }
+        candidates
+    }
+
+    /// Turns the query into a filter chip shown before the query, so the
+    /// next query searches within its matches. Returns whether there was a
+    /// valid query to promote.
+    fn promote_query(&mut self) -> bool {
+        if self.prompt.is_empty() || self.query_error.is_some() {
+            return false;
+        }
+        self.chips.push((std::mem::take(&mut self.prompt), self.match_mode));
+        // Cached matches were computed without the new chip.
+        self.match_cache.clear();
+        self.refilter();
+        true
+    }
+
+    /// Makes the current matches the items searched by the next query.
+    /// Returns whether there was a query narrowing the items.
+    fn narrow(&mut self) -> bool {
+        if self.prompt.is_empty() || self.query_error.is_some() {
+            return false;
+        }
+        let mut matched: Vec<usize> = self.matches.iter()
+            .filter(|matched| matched.header.is_none())
+            .map(|matched| matched.index)
+            .collect();
+        matched.sort_unstable();
+        self.narrowed = Some(matched);
+        self.prompt.clear();
+        self.match_cache.clear();
+        self.refilter();
+        true
+    }
+
+    /// Searches all items again after `narrow()`. Returns whether the
+    /// items were narrowed.
+    fn widen(&mut self) -> bool {
+        if self.narrowed.take().is_none() {
+            return false;
+        }
+        self.match_cache.clear();
+        self.refilter();
+        true
+    }
+
+    /// Removes the filter chip at `position`, widening the matches again.
+    fn remove_chip(&mut self, position: usize) {
+        self.chips.remove(position);
+        self.match_cache.clear();
+        self.refilter();
+    }
+
+    /// Returns the position of the filter chip drawn at `column` of the
+    /// prompt line.
+    fn chip_at_column(&self, column: u16) -> Option<usize> {
+        let mut start = text::width(&self.prompt_prefix()) - text::width(&self.chips_text());
+        for (position, (query, _)) in self.chips.iter().enumerate() {
+            let end = start + text::width(query) + 2;
+            if (start..end).contains(&(column as usize)) {
+                return Some(position);
+            }
+            start = end + 1;
+        }
+        None
+    }
+
+    /// Returns the filter chips as shown after the prompt label.
+    fn chips_text(&self) -> String {
+        self.chips.iter().map(|(query, _)| format!("[{}] ", query)).collect()
+    }
+
+    /// Derives the display and searchable strings of the first `count`
+    /// items that have not been cached yet, widening the columns to fit.
+    fn cache_item_texts(&mut self, count: usize) {
+        for index in self.item_texts.len()..count {
+            let display = OnceCell::new();
+            if !self.columns.is_empty() {
+                let plain = self.plain_text(display.get_or_init(|| format!("{}", self.items[index])));
+                let fields = columns::split(&plain, self.delimiter.as_deref(), self.columns.len());
+                columns::measure(&mut self.column_widths, &fields);
+            }
+            let searchable = match &self.match_key {
+                Some(key) => key(&self.items[index]).to_lowercase().into(),
+                None => {
+                    let display = display.get_or_init(|| format!("{}", self.items[index]));
+                    self.searchable_text(&self.plain_text(display)).to_lowercase().into()
+                },
+            };
+            let duplicate = self.dedupe && {
+                let display = display.get_or_init(|| format!("{}", self.items[index]));
+                !self.seen_displays.insert(display.clone())
+            };
+            self.item_texts.push(ItemText { display, searchable, duplicate });
+        }
+    }
+
+    /// Returns the display string of the item at `index`, producing it if
+    /// it was not needed for matching.
+    fn item_display(&self, index: usize) -> &str {
+        self.item_texts[index].display.get_or_init(|| format!("{}", self.items[index]))
+    }
+
+    /// Forgets the cached item strings and matches after the item list
+    /// was replaced or edited.
+    fn invalidate_item_texts(&mut self) {
+        self.item_texts.clear();
+        self.seen_displays.clear();
+        self.match_cache.clear();
+        if self.match_job.take().is_some() {
+            // The items being scored are gone; match the new ones instead.
+            self.ranker.cancel();
+            self.pending_filter.get_or_insert_with(Instant::now);
+        }
+        // Narrowed items are positions in the old list.
+        self.narrowed = None;
+        self.column_widths = self.header_widths();
+    }
+
+    /// Returns the display string of an item, or the name of a group.
+    fn text<'a>(&'a self, matched: &'a Match) -> &'a str {
+        match &matched.header {
+            Some(name) => name,
+            None => self.item_display(matched.index),
+        }
+    }
+
+    /// Replaces the matches, inserting section headers when grouping.
+    fn set_matches(&mut self, matches: Vec<Match>) {
+        self.num_of_matches = matches.len();
+        self.matches = match &self.group_by {
+            Some(group_by) => {
+                let mut groups: Vec<(String, Vec<Match>)> = Vec::new();
+                let mut positions = HashMap::new();
+                for matched in matches {
+                    let name = group_by(&self.items[matched.index]);
+                    let position = *positions.entry(name.clone()).or_insert_with(|| {
+                        groups.push((name, Vec::new()));
+                        groups.len() - 1
+                    });
+                    groups[position].1.push(matched);
+                }
+                groups.sort_by_key(|(_, members)| members.iter().map(|matched| matched.index).min());
+                groups.into_iter()
+                    .flat_map(|(name, members)| {
+                        let header = Match { index: members[0].index, header: Some(name) };
+                        std::iter::once(header).chain(members)
+                    })
+                    .collect()
+            },
+            None => matches,
+        };
+        self.num_of_items = self.matches.len();
+    }
+
+    fn plain_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.ansi {
+            ansi::strip(text)
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    /// Splits plain `text` into one field per column.
+    fn fields<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        columns::split(text, self.delimiter.as_deref(), self.columns.len())
+    }
+
+    /// Returns the part of plain `text` the query is matched against.
+    fn searchable_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if let Some(nth) = &self.nth {
+            return Cow::Owned(nth.select(text, self.delimiter.as_deref()));
+        }
+        if self.columns.iter().all(Column::is_searchable) {
+            return Cow::Borrowed(text);
+        }
+        let fields = self.fields(text);
+        Cow::Owned(self.columns.iter()
+            .zip(fields)
+            .filter(|(column, _)| column.is_searchable())
+            .map(|(_, field)| field)
+            .collect::<Vec<_>>()
+            .join(" "))
+    }
+
+    /// Returns the line shown for an item with display string `text`.
+    fn display_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.columns.is_empty() {
+            return Cow::Borrowed(text);
+        }
+        let plain = self.plain_text(text);
+        Cow::Owned(columns::format_row(&self.columns, &self.column_widths, &self.fields(&plain)))
+    }
+
+    /// Returns the widths of the column headers.
+    fn header_widths(&self) -> Vec<usize> {
+        self.columns.iter().map(|column| text::width(column.header())).collect()
+    }
+
+    /// Returns whether the row of column titles is shown.
+    fn has_column_header(&self) -> bool {
+        self.columns.iter().any(|column| !column.header().is_empty())
+    }
+
+    /// Returns the number of rows between the prompt and the item list:
+    /// the header lines followed by the column titles.
+    fn header_rows(&self) -> usize {
+        self.header.len() + usize::from(self.has_column_header())
+    }
+
+    /// Returns the number of rows available for items and a preview pane
+    /// below them.
+    fn list_area_height(&self) -> usize {
+        let footer_rows = usize::from(self.footer.is_some());
+        self.height.saturating_sub(1 + self.header_rows() + footer_rows)
+    }
+
+    /// Returns the number of rows available for items.
+    fn list_height(&self) -> usize {
+        self.list_area_height() - self.preview_height()
+    }
+
+    /// Returns whether the terminal is too small for the full layout, in
+    /// which case only the prompt line is drawn, followed by the
+    /// highlighted item.
+    fn compact(&self) -> bool {
+        self.list_height() == 0 || self.width < MIN_FULL_WIDTH
+    }
+
+    /// Returns the text of the highlighted item shown after the prompt in
+    /// the compact layout.
+    fn compact_item(&self) -> Option<String> {
+        let matched = self.selected_match()?;
+        let item = self.display_text(self.item_display(matched.index));
+        Some(self.plain_text(&item).into_owned())
+    }
+
+    /// Returns the current spinner frame while items are still streaming in.
+    fn spinner(&self) -> Option<char> {
+        self.item_source.as_ref()?;
+        if self.accessible {
+            // A static frame, so loading does not cause constant redraws.
+            return Some(SPINNER_FRAMES[0]);
+        }
+        let frame = self.started.elapsed().as_millis() / SPINNER_INTERVAL.as_millis();
+        Some(SPINNER_FRAMES[frame as usize % SPINNER_FRAMES.len()])
+    }
+
+    /// Returns the lines of the key binding help: each action followed by
+    /// the keys bound to it, in alphabetical order.
+    fn help_lines(&self) -> Vec<String> {
+        let mut bindings: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (key, action) in self.keymap.iter() {
+            // Item actions and external commands take precedence over the keymap.
+            if !self.item_actions.contains_key(key) && !self.external_commands.contains_key(key) {
+                bindings.entry(action.to_string()).or_default().push(key.to_string());
+            }
+        }
+        for key in self.item_actions.keys() {
+            bindings.entry(String::from("item action")).or_default().push(key.to_string());
+        }
+        for key in self.external_commands.keys() {
+            if !self.item_actions.contains_key(key) {
+                bindings.entry(String::from("external command")).or_default().push(key.to_string());
+            }
+        }
+        let width = bindings.keys().map(|action| action.chars().count()).max().unwrap_or_default();
+        bindings.into_iter()
+            .map(|(action, mut keys)| {
+                keys.sort();
+                format!("{:width$}  {}", action, keys.join(", "))
+            })
+            .collect()
+    }
+
+    /// Returns the text shown at the end of the prompt line: the match
+    /// count, preceded by a spinner while loading, then whether the items
+    /// are narrowed and the match mode when it isn't the default, followed
+    /// by the status text.
+    fn prompt_info(&self) -> String {
+        let total = self.narrowed.as_ref().map_or(self.items.len(), Vec::len);
+        let mut count = match self.spinner() {
+            Some(frame) => format!("{} {}/{}", frame, self.num_of_matches, total),
+            None => format!("{}/{}", self.num_of_matches, total),
+        };
+        match self.max_selections {
+            Some(max) if self.multi_select => count.push_str(&format!(" ({}/{})", self.marked.len(), max)),
+            _ if self.multi_select && !self.marked.is_empty() => {
+                count.push_str(&format!(" ({})", self.marked.len()));
+            },
+            _ => {},
+        }
+        let mode = match self.match_mode {
+            MatchMode::Fuzzy => "",
+            mode => mode.label(),
+        };
+        let narrowed = if self.narrowed.is_some() { "narrowed" } else { "" };
+        [count.as_str(), narrowed, mode, self.status_text.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    fn prompt_row(&self) -> u16 {
+        match self.layout {
+            Layout::Default => self.top,
+            Layout::Reverse => self.top + self.height.saturating_sub(1) as u16,
+        }
+    }
+
+    /// Returns the terminal row of the footer, at the opposite end from the prompt.
+    fn footer_row(&self) -> u16 {
+        match self.layout {
+            Layout::Default => self.top + self.height.saturating_sub(1) as u16,
+            Layout::Reverse => self.top,
+        }
+    }
+
+    /// Returns the terminal row of the header line at `line`, counting
+    /// outward from the prompt.
+    fn header_row(&self, line: usize) -> u16 {
+        match self.layout {
+            Layout::Default => self.top + (line + 1) as u16,
+            Layout::Reverse => self.prompt_row().saturating_sub((line + 1) as u16),
+        }
+    }
+
+    /// Returns the number of rows between the prompt and the first item:
+    /// the prompt and header rows, and in the reverse layout a preview
+    /// pane below the items.
+    fn list_gap(&self) -> u16 {
+        let preview_rows = match self.layout {
+            Layout::Default => 0,
+            Layout::Reverse => self.preview_height(),
+        };
+        (self.header_rows() + 1 + preview_rows) as u16
+    }
+
+    /// Returns the terminal row of the visible item at `offset` from the top of the list.
+    fn item_row(&self, offset: usize) -> u16 {
+        let gap = self.list_gap();
+        match self.layout {
+            Layout::Default => self.top + gap + offset as u16,
+            Layout::Reverse => self.prompt_row().saturating_sub(gap + offset as u16),
+        }
+    }
+
+    /// Maps a terminal row to the index of the matched item displayed on it.
+    fn index_at_row(&self, row: u16) -> Option<usize> {
+        let gap = self.list_gap();
+        let offset = match self.layout {
+            Layout::Default => row.checked_sub(self.top + gap)?,
+            Layout::Reverse => self.prompt_row().checked_sub(row + gap)?,
+        } as usize;
+        let visible = self.num_of_displayable_items
+            .min(self.num_of_items.saturating_sub(self.start_index));
+        let mut first_line = 0;
+        for position in self.start_index..self.start_index + visible {
+            first_line += self.item_lines(position);
+            if offset < first_line {
+                return Some(position);
+            }
+        }
+        None
+    }
+
+    /// Returns the number of columns available for item text.
+    fn wrap_width(&self) -> usize {
+        self.list_width().saturating_sub(2 + self.decoration_indent).max(1)
+    }
+
+    /// Returns the number of rows taken by the match at `position`.
+    fn item_lines(&self, position: usize) -> usize {
+        match self.matches.get(position) {
+            Some(matched) if self.line_wrap && matched.header.is_none() => {
+                let length = text::width(&self.plain_text(&self.display_text(self.text(matched))));
+                length.div_ceil(self.wrap_width()).max(1)
+            },
+            _ => 1,
+        }
+    }
+
+    /// Recomputes the visible window for wrapped items of varying height,
+    /// keeping the selection on screen.
+    fn fit_wrapped_viewport(&mut self) {
+        if !self.line_wrap || self.num_of_items == 0 {
+            return;
+        }
+        let height = self.list_height();
+        self.start_index = self.start_index.min(self.selected);
+        while self.start_index < self.selected
+            && (self.start_index..=self.selected).map(|position| self.item_lines(position)).sum::<usize>() > height
+        {
+            self.start_index += 1;
+        }
+        let mut rows = 0;
+        self.end_index = self.start_index;
+        for position in self.start_index..self.num_of_items {
+            rows += self.item_lines(position);
+            if rows > height && position > self.start_index {
+                break;
+            }
+            self.end_index = position;
+        }
+        self.num_of_displayable_items = self.end_index - self.start_index + 1;
+    }
+
+}
+
+/// Turns pasted text into a single line for the query: line breaks and
+/// tabs become spaces, other control characters are dropped, and a
+/// trailing line break, as copied along with a whole line, is ignored.
+fn paste_text(text: &str) -> String {
+    text.trim_end_matches(['\r', '\n'])
+        .replace("\r\n", "\n")
+        .chars()
+        .filter_map(|ch| match ch {
+            '\n' | '\r' | '\t' => Some(' '),
+            ch if ch.is_control() => None,
+            ch => Some(ch),
+        })
+        .collect()
+}
+
+/// Lets the user pick one of `items` with the default settings, in one call.
+///
+/// Returns `Ok(None)` if the selection is cancelled. Use [`FuzzyPicker`]
+/// directly to customize the picker.
+///
+/// # Example
+///
+/// ```no_run
+/// if let Ok(Some(fruit)) = fuzzypicker::pick(["apple", "banana", "cherry"]) {
+///     println!("{}", fruit);
+/// }
+/// ```
+pub fn pick<T: Display + Clone>(items: impl IntoIterator<Item = T>) -> Result<Option<T>, PickError> {
+    Ok(FuzzyPicker::from_items(items).pick()?)
+}
+
+impl PickerState<String> {
+    /// Reads newline-delimited items from `reader` on a background thread,
+    /// streaming them into the picker as they arrive.
+    ///
+    /// This supports `producer | my-tool` pipelines: items come from the
+    /// redirected stdin while key events are still read from the terminal.
+    /// Combine with [`FuzzyPicker::set_tty_path`] when stdout is redirected too.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The source of items, e.g. `std::io::stdin()`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let mut picker = FuzzyPicker::<String>::new(&[]);
+    /// picker.read_items(std::io::stdin());
+    /// if let Ok(Some(line)) = picker.pick() {
+    ///     println!("{}", line);
+    /// }
+    /// ```
+    pub fn read_items<R: Read + Send + 'static>(&mut self, reader: R) {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        self.set_item_source(receiver);
+    }
+
+    /// Like [`read_items`](Self::read_items), but reads items separated by
+    /// `delimiter` instead of newlines. Pass `b'\0'` to read the output of
+    /// `find -print0` and similar, so file names containing newlines stay
+    /// intact. Invalid UTF-8 is replaced with `U+FFFD`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The source of items.
+    /// * `delimiter` - The byte ending each item.
+    pub fn read_items_delimited<R: Read + Send + 'static>(&mut self, reader: R, delimiter: u8) {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for item in BufReader::new(reader).split(delimiter).map_while(Result::ok) {
+                if sender.send(String::from_utf8_lossy(&item).into_owned()).is_err() {
+                    break;
+                }
+            }
+        });
+        self.set_item_source(receiver);
+    }
+}
@@ -0,0 +1,255 @@
+//! Matching and ranking of items against the query.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "regex")]
+use regex::{Regex, RegexBuilder};
+use crate::MATCH_CHUNK_SIZE;
+
+/// How matching items are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum RankingMode {
+    /// Fuzzy match and order items by score, best match first.
+    #[default]
+    Score,
+    /// Keep items whose text contains the query, in their original order.
+    /// Skips fuzzy scoring entirely.
+    PreserveOrder,
+    /// Fuzzy match and order items alphabetically by display string,
+    /// ignoring case.
+    Alphabetical,
+    /// Fuzzy match and keep items in the order they were matched.
+    Unsorted,
+}
+
+/// How the query is matched against items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum MatchMode {
+    /// Items containing the query's characters in order, not necessarily adjacent.
+    #[default]
+    Fuzzy,
+    /// Items containing the query as a substring.
+    Exact,
+    /// Items starting with the query.
+    Prefix,
+    /// Items matching the query as a case-insensitive regular expression.
+    #[cfg(feature = "regex")]
+    Regex,
+}
+
+impl MatchMode {
+    /// Returns the mode selected after this one by `Action::CycleMatchMode`.
+    pub(crate) fn next(self) -> Self {
+        match self {
+            MatchMode::Fuzzy => MatchMode::Exact,
+            MatchMode::Exact => MatchMode::Prefix,
+            #[cfg(feature = "regex")]
+            MatchMode::Prefix => MatchMode::Regex,
+            #[cfg(not(feature = "regex"))]
+            MatchMode::Prefix => MatchMode::Fuzzy,
+            #[cfg(feature = "regex")]
+            MatchMode::Regex => MatchMode::Fuzzy,
+        }
+    }
+
+    /// Returns the name shown in the prompt line while the mode is active.
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            MatchMode::Fuzzy => "fuzzy",
+            MatchMode::Exact => "exact",
+            MatchMode::Prefix => "prefix",
+            #[cfg(feature = "regex")]
+            MatchMode::Regex => "regex",
+        }
+    }
+}
+
+/// A query prepared for matching against many items.
+pub(crate) struct Pattern {
+    mode: MatchMode,
+    query: String,
+    #[cfg(feature = "regex")]
+    regex: Option<Regex>,
+}
+
+impl Pattern {
+    /// Prepares `query` for matching in `mode`, returning a short message
+    /// if the query is not a valid pattern for that mode.
+    pub(crate) fn new(query: &str, mode: MatchMode) -> Result<Self, String> {
+        #[cfg(feature = "regex")]
+        let regex = match mode {
+            MatchMode::Regex if !query.is_empty() => Some(
+                RegexBuilder::new(query)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|_| String::from("invalid regex"))?
+            ),
+            _ => None,
+        };
+        Ok(Self {
+            mode,
+            query: query.to_lowercase(),
+            #[cfg(feature = "regex")]
+            regex,
+        })
+    }
+
+    /// Matches lowercase `text` against the pattern, returning the item's
+    /// score if it matches.
+    ///
+    /// Exact and prefix matches are still scored with the fuzzy matcher so
+    /// `RankingMode::Score` orders them sensibly.
+    pub(crate) fn score(&self, matcher: &SkimMatcherV2, text: &str) -> Option<i64> {
+        let query = self.query.as_str();
+        if query.is_empty() {
+            return Some(0);
+        }
+        match self.mode {
+            MatchMode::Fuzzy => matcher.fuzzy_match(text, query).filter(|&score| score != 0),
+            MatchMode::Exact if !text.contains(query) => None,
+            MatchMode::Prefix if !text.starts_with(query) => None,
+            MatchMode::Exact | MatchMode::Prefix => Some(matcher.fuzzy_match(text, query).unwrap_or_default()),
+            #[cfg(feature = "regex")]
+            MatchMode::Regex => self.regex.as_ref()?.is_match(text).then_some(0),
+        }
+    }
+
+    /// Matches each of the lowercase `texts`, in parallel with the `rayon`
+    /// feature. With `substring_only`, items are only checked for
+    /// containing the query and are not scored.
+    pub(crate) fn score_all(&self, matcher: &SkimMatcherV2, texts: &[&str], substring_only: bool) -> Vec<Option<i64>> {
+        let score = |text: &&str| if substring_only {
+            text.contains(&self.query).then_some(0)
+        } else {
+            self.score(matcher, text)
+        };
+        #[cfg(feature = "rayon")]
+        return texts.par_iter().map(score).collect();
+        #[cfg(not(feature = "rayon"))]
+        return texts.iter().map(score).collect();
+    }
+}
+
+/// Scores items on a background thread, so typing is not held up while
+/// many items are matched.
+///
+/// Every job gets a new generation. Workers of older jobs stop between
+/// chunks of items, and results they sent before noticing are dropped.
+pub(crate) struct Ranker {
+    /// Generation of the latest job.
+    generation: Arc<AtomicU64>,
+    sender: Sender<(u64, Vec<Option<i64>>)>,
+    receiver: Receiver<(u64, Vec<Option<i64>>)>,
+}
+
+impl Default for Ranker {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { generation: Arc::default(), sender, receiver }
+    }
+}
+
+impl Ranker {
+    /// Starts scoring the lowercase `texts` against `pattern`, abandoning
+    /// the previous job. See [`Pattern::score_all`] for `substring_only`.
+    pub(crate) fn start(&mut self, pattern: Pattern, texts: Vec<Arc<str>>, substring_only: bool) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let latest = Arc::clone(&self.generation);
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let matcher = SkimMatcherV2::default();
+            let mut scores = Vec::with_capacity(texts.len());
+            for chunk in texts.chunks(MATCH_CHUNK_SIZE) {
+                if latest.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                let chunk: Vec<&str> = chunk.iter().map(|text| &**text).collect();
+                scores.extend(pattern.score_all(&matcher, &chunk, substring_only));
+            }
+            let _ = sender.send((generation, scores));
+        });
+    }
+
+    /// Abandons the running job, if any.
+    pub(crate) fn cancel(&mut self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Returns the scores of the latest job once it has finished, in the
+    /// order of its texts.
+    pub(crate) fn finished(&mut self) -> Option<Vec<Option<i64>>> {
+        let latest = self.generation.load(Ordering::SeqCst);
+        self.receiver.try_iter()
+            .filter(|(generation, _)| *generation == latest)
+            .last()
+            .map(|(_, scores)| scores)
+    }
+}
+
+/// Items matched by earlier queries, used to narrow the search while the
+/// query grows.
+///
+/// Appending a character to the query can only remove matches in every
+/// mode but regex, so only the previous matches need to be checked again.
+/// Deleting it falls back to the matches of a shorter query.
+#[derive(Default)]
+pub(crate) struct MatchCache {
+    entries: Vec<CacheEntry>,
+}
+
+struct CacheEntry {
+    query: String,
+    mode: MatchMode,
+    substring_only: bool,
+    /// Number of items when the query was matched. Items received later
+    /// have not been checked against the query.
+    num_of_items: usize,
+    /// Indexes of the matching items, in ascending order.
+    matched: Vec<usize>,
+}
+
+impl MatchCache {
+    /// Returns the indexes of the items that can match the lowercase
+    /// `query` among those the cached matches were computed from, along
+    /// with their number, or `None` if every item has to be checked. Items
+    /// received since then are left to the caller to check.
+    pub(crate) fn candidates(&mut self, query: &str, mode: MatchMode, substring_only: bool) -> Option<(Vec<usize>, usize)> {
+        while let Some(entry) = self.entries.last() {
+            if entry.mode == mode && entry.substring_only == substring_only && query.starts_with(&entry.query) {
+                break;
+            }
+            self.entries.pop();
+        }
+        #[cfg(feature = "regex")]
+        if mode == MatchMode::Regex {
+            return None;
+        }
+        let entry = self.entries.last()?;
+        Some((entry.matched.clone(), entry.num_of_items))
+    }
+
+    /// Remembers the items matched by `query`.
+    pub(crate) fn insert(&mut self, query: &str, mode: MatchMode, substring_only: bool, num_of_items: usize, matched: Vec<usize>) {
+        #[cfg(feature = "regex")]
+        if mode == MatchMode::Regex {
+            return;
+        }
+        if self.entries.last().is_some_and(|entry| entry.query == query) {
+            self.entries.pop();
+        }
+        self.entries.push(CacheEntry { query: query.to_string(), mode, substring_only, num_of_items, matched });
+    }
+
+    /// Forgets all matches, e.g. after the item list was replaced.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
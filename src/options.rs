@@ -0,0 +1,107 @@
+//! Default options read from the environment, like fzf's `FZF_DEFAULT_OPTS`.
+
+use std::fmt::Display;
+use crate::{FuzzyPicker, Layout, MatchMode};
+
+/// Environment variable holding options applied to every [`FuzzyPicker`]
+/// when it is constructed, e.g. `FUZZYPICKER_DEFAULT_OPTS="--reverse --height 40%"`.
+///
+/// This lets users impose their preferred look on all applications using
+/// the crate. Settings made by the application afterwards take precedence.
+///
+/// Supported options, as `--option value` or `--option=value`:
+///
+/// - `--reverse`, or `--layout default|reverse`, named as in fzf: the
+///   prompt is at the bottom by default and at the top with `--reverse`
+/// - `--height <rows>` or `--height <percent>%` of the terminal
+/// - `--prompt <label>`
+/// - `--no-mouse`
+/// - `--ansi`
+/// - `--wrap`
+/// - `--exact`
+///
+/// Values containing spaces can be quoted with `'` or `"`. Unknown
+/// options and invalid values are ignored.
+pub const DEFAULT_OPTS_ENV: &str = "FUZZYPICKER_DEFAULT_OPTS";
+
+/// Splits `opts` at whitespace outside of single or double quotes.
+fn split_words(opts: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for ch in opts.chars() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => word.push(ch),
+            None if ch == '\'' || ch == '"' => {
+                quote = Some(ch);
+                in_word = true;
+            },
+            None if ch.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            },
+            None => {
+                word.push(ch);
+                in_word = true;
+            },
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// Parses a `--height` value: a number of rows, or a percentage of
+/// `terminal_height` rows.
+fn parse_height(value: &str, terminal_height: u16) -> Option<u16> {
+    match value.strip_suffix('%') {
+        Some(percent) => {
+            let percent: u32 = percent.parse().ok().filter(|&percent| percent <= 100)?;
+            Some((terminal_height as u32 * percent / 100).max(1) as u16)
+        },
+        None => value.parse().ok().filter(|&rows| rows > 0),
+    }
+}
+
+impl<T: Display + Clone> FuzzyPicker<T> {
+    /// Applies options in the format of [`DEFAULT_OPTS_ENV`].
+    pub(crate) fn apply_default_opts(&mut self, opts: &str, terminal_height: u16) {
+        let mut words = split_words(opts).into_iter();
+        while let Some(word) = words.next() {
+            let (option, inline_value) = match word.split_once('=') {
+                Some((option, value)) => (option.to_string(), Some(value.to_string())),
+                None => (word, None),
+            };
+            let mut value = || inline_value.clone().or_else(|| words.next());
+            match option.as_str() {
+                // fzf's layouts are named after the list, ours after the prompt.
+                "--reverse" => self.set_layout(Layout::Default),
+                "--layout" => match value().as_deref() {
+                    Some("default") => self.set_layout(Layout::Reverse),
+                    Some("reverse") => self.set_layout(Layout::Default),
+                    _ => {},
+                },
+                "--height" => {
+                    if let Some(rows) = value().and_then(|value| parse_height(&value, terminal_height)) {
+                        self.set_height(rows);
+                    }
+                },
+                "--prompt" => {
+                    if let Some(label) = value() {
+                        self.set_prompt_label(label);
+                    }
+                },
+                "--no-mouse" => self.enable_mouse(false),
+                "--ansi" => self.set_ansi(true),
+                "--wrap" => self.set_line_wrap(true),
+                "--exact" => self.set_match_mode(MatchMode::Exact),
+                _ => {},
+            }
+        }
+    }
+}
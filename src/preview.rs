@@ -0,0 +1,527 @@
+//! The preview pane showing details of the highlighted item.
+
+use std::fmt::Display;
+#[cfg(feature = "preview-command")]
+use std::io::{BufRead, BufReader, Read};
+#[cfg(feature = "preview-command")]
+use std::process::{Child, Command, Stdio};
+#[cfg(feature = "preview-command")]
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+#[cfg(feature = "preview-command")]
+use std::thread;
+use crate::{ansi, text, ItemFormatter, Layout, PickerState};
+
+/// Maximum number of lines kept from a preview, so commands with endless
+/// output are stopped.
+const PREVIEW_MAX_LINES: usize = 10_000;
+
+/// Maximum number of characters kept from a line of a preview.
+const PREVIEW_MAX_LINE_WIDTH: usize = 1000;
+
+/// Number of bytes at the start of a preview inspected by [`looks_binary`].
+const BINARY_PROBE_LEN: usize = 8000;
+
+/// Returns whether `bytes` look like binary data, such as an image, rather
+/// than text: their start contains a NUL byte, or control bytes make up
+/// more than a third of it.
+///
+/// Binary previews are replaced with a short summary, as printing them
+/// would garble the terminal.
+///
+/// # Example
+///
+/// ```
+/// assert!(fuzzypicker::looks_binary(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+/// assert!(!fuzzypicker::looks_binary("fn main() {}\n".as_bytes()));
+/// ```
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    let probe = &bytes[..bytes.len().min(BINARY_PROBE_LEN)];
+    if probe.contains(&0) {
+        return true;
+    }
+    let controls = probe.iter()
+        .filter(|&&byte| (byte < 0x20 && !b"\t\n\r\x0c\x1b".contains(&byte)) || byte == 0x7f)
+        .count();
+    controls * 3 > probe.len()
+}
+
+/// Makes preview text safe to print: keeps colors set with ANSI escape
+/// sequences, removes other escape sequences, expands tabs, and replaces
+/// other control characters with `�`. Returns one string per line.
+///
+/// Text that [`looks_binary`] is summarized as a single line, and very
+/// long text or lines are cut short.
+///
+/// # Example
+///
+/// ```
+/// use fuzzypicker::sanitize_preview;
+///
+/// assert_eq!(sanitize_preview("a\tb\x07\nc"), ["a   b�", "c"]);
+/// assert_eq!(sanitize_preview("\0\0\0"), ["binary content, 3 bytes"]);
+/// ```
+pub fn sanitize_preview(text: &str) -> Vec<String> {
+    if looks_binary(text.as_bytes()) {
+        return vec![binary_summary(text.len())];
+    }
+    let mut lines = text.lines();
+    let mut sanitized: Vec<String> = lines.by_ref().take(PREVIEW_MAX_LINES).map(clean_line).collect();
+    let rest = lines.count();
+    if rest > 0 {
+        sanitized.push(format!("… {} more lines", rest));
+    }
+    sanitized
+}
+
+/// Returns the line shown in place of binary content of `len` bytes.
+fn binary_summary(len: usize) -> String {
+    format!("binary content, {} bytes", len)
+}
+
+/// Placement of the preview pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum PreviewPosition {
+    /// To the right of the item list.
+    #[default]
+    Right,
+    /// Below the item list.
+    Bottom,
+}
+
+/// Where the preview of an item comes from.
+pub(crate) enum Preview<T> {
+    /// Text produced by a callback.
+    Text(ItemFormatter<T>),
+    /// The output of a shell command, with `{}` standing for the item.
+    #[cfg(feature = "preview-command")]
+    Command(String),
+}
+
+/// Output of a preview command, sent from the threads reading it.
+#[cfg(feature = "preview-command")]
+enum Output {
+    Line(String),
+    /// The command printed binary data, so reading stopped.
+    Binary,
+}
+
+/// A preview command running for the highlighted item.
+#[cfg(feature = "preview-command")]
+pub(crate) struct PreviewJob {
+    child: Child,
+    lines: Receiver<Output>,
+}
+
+#[cfg(feature = "preview-command")]
+impl PreviewJob {
+    /// Runs `command` through the shell, streaming its stdout and stderr.
+    fn spawn(command: &str) -> std::io::Result<Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(command, "running preview command");
+        let mut child = shell(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let (sender, lines) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            forward_lines(stdout, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward_lines(stderr, sender);
+        }
+        Ok(Self { child, lines })
+    }
+}
+
+#[cfg(feature = "preview-command")]
+impl Drop for PreviewJob {
+    /// Kills the command, e.g. because another item was highlighted.
+    fn drop(&mut self) {
+        // SAFETY: kill() has no preconditions. The shell is not waited
+        // for yet, so its process group still belongs to the command.
+        #[cfg(unix)]
+        unsafe {
+            // The group also holds the programs the shell started.
+            libc::kill(-(self.child.id() as libc::pid_t), libc::SIGKILL);
+        }
+        #[cfg(not(unix))]
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Returns a command running `command` through the platform's shell.
+#[cfg(feature = "preview-command")]
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        // A process group of its own, so the whole command can be killed.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+        shell
+    }
+}
+
+/// Quotes `text` as a single shell word.
+#[cfg(feature = "preview-command")]
+fn quote(text: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    }
+}
+
+/// Sends the lines read from `reader` on a background thread, until
+/// either side is closed or binary data is read. Invalid UTF-8 is replaced
+/// rather than ending the preview, and overly long lines are split.
+#[cfg(feature = "preview-command")]
+fn forward_lines(reader: impl Read + Send + 'static, sender: mpsc::Sender<Output>) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let limit = (PREVIEW_MAX_LINE_WIDTH * 4) as u64;
+            match reader.by_ref().take(limit).read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) if looks_binary(&line) => {
+                    let _ = sender.send(Output::Binary);
+                    break;
+                },
+                Ok(_) => {
+                    if sender.send(Output::Line(String::from_utf8_lossy(&line).into_owned())).is_err() {
+                        break;
+                    }
+                },
+            }
+        }
+    });
+}
+
+/// Makes a line of preview output printable on a single row, keeping its
+/// colors.
+fn clean_line(line: &str) -> String {
+    let line = line.trim_end_matches(['\n', '\r']);
+    let line = ansi::sanitize(line, 4);
+    if line.len() > PREVIEW_MAX_LINE_WIDTH && text::width(&ansi::strip(&line)) > PREVIEW_MAX_LINE_WIDTH {
+        ansi::slice(&line, 0, PREVIEW_MAX_LINE_WIDTH)
+    } else {
+        line
+    }
+}
+
+/// Returns the first `width` columns of a preview line, in color if
+/// `colors` is set.
+fn fit_line(line: &str, width: usize, colors: bool) -> String {
+    if colors && line.contains('\x1b') {
+        ansi::slice(line, 0, width)
+    } else {
+        text::slice(&ansi::strip(line), 0, width)
+    }
+}
+
+impl<T: Display + Clone> PickerState<T> {
+    /// Shows the text produced by `preview` for the highlighted item in a
+    /// pane to the right of the list, which takes half of the width unless
+    /// placed with [`set_preview_window`](Self::set_preview_window). Long
+    /// previews scroll with `Shift-Up` and `Shift-Down` or the mouse wheel.
+    ///
+    /// The text may be colored with ANSI escape sequences.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let mut picker = FuzzyPicker::new(&["Cargo.toml", "README.md"]);
+    /// picker.set_preview(|path| std::fs::read_to_string(path).unwrap_or_default());
+    /// let picked = picker.pick();
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `preview` - Produces the preview text of an item.
+    pub fn set_preview(&mut self, preview: impl Fn(&T) -> String + 'static) {
+        self.preview = Some(Preview::Text(Box::new(preview)));
+        self.stop_preview();
+        self.relayout();
+    }
+
+    /// Shows the output of a shell command for the highlighted item in the
+    /// preview pane, like `fzf --preview`. Every `{}` in `template` is
+    /// replaced with the quoted item.
+    ///
+    /// The output appears as it is produced. When another item is
+    /// highlighted the command is killed and run again for that item.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let mut picker = FuzzyPicker::new(&["Cargo.toml", "README.md"]);
+    /// picker.set_preview_command("head -n 50 {}");
+    /// let picked = picker.pick();
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - The command line, run with `sh -c` (`cmd /C` on Windows).
+    #[cfg(feature = "preview-command")]
+    pub fn set_preview_command(&mut self, template: impl Into<String>) {
+        self.preview = Some(Preview::Command(template.into()));
+        self.stop_preview();
+        self.relayout();
+    }
+
+    /// Places the preview pane to the right of or below the item list.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Where to show the pane. Defaults to `PreviewPosition::Right`.
+    /// * `size` - The percentage of the width, or of the rows below the
+    ///   prompt, taken by the pane, from 1 to 99. Defaults to 50.
+    pub fn set_preview_window(&mut self, position: PreviewPosition, size: u16) {
+        self.preview_position = position;
+        self.preview_size = size.clamp(1, 99);
+        self.relayout();
+    }
+
+    /// Hides or shows the preview pane. It can also be toggled while
+    /// picking with `Alt-P` (`Action::TogglePreview`).
+    ///
+    /// # Arguments
+    ///
+    /// * `hidden` - Whether to hide the pane. Defaults to `false`.
+    pub fn set_preview_hidden(&mut self, hidden: bool) {
+        self.preview_hidden = hidden;
+        self.relayout();
+    }
+
+    /// Returns whether the preview pane is shown.
+    fn preview_visible(&self) -> bool {
+        self.preview.is_some() && !self.preview_hidden
+    }
+
+    /// Returns the number of columns taken by a preview pane to the right
+    /// of the list, including its border.
+    pub(crate) fn preview_width(&self) -> usize {
+        match self.preview_position {
+            PreviewPosition::Right if self.preview_visible() => self.width * self.preview_size as usize / 100,
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of rows taken by a preview pane below the list,
+    /// including its border.
+    pub(crate) fn preview_height(&self) -> usize {
+        match self.preview_position {
+            PreviewPosition::Bottom if self.preview_visible() => {
+                self.list_area_height() * self.preview_size as usize / 100
+            },
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of preview lines shown at once.
+    fn preview_text_height(&self) -> usize {
+        match self.preview_position {
+            PreviewPosition::Right => self.list_height(),
+            PreviewPosition::Bottom => self.preview_height().saturating_sub(1),
+        }
+    }
+
+    /// Returns the terminal row of the top of the preview pane.
+    fn preview_top(&self) -> u16 {
+        let gap = (self.header_rows() + 1) as u16;
+        match (self.preview_position, self.layout) {
+            (PreviewPosition::Right, _) => {
+                self.item_row(0).min(self.item_row(self.list_height().saturating_sub(1)))
+            },
+            (PreviewPosition::Bottom, Layout::Default) => self.top + gap + self.list_height() as u16,
+            (PreviewPosition::Bottom, Layout::Reverse) => {
+                (self.prompt_row() + 1).saturating_sub(gap + self.preview_height() as u16)
+            },
+        }
+    }
+
+    /// Returns the text of the preview pane as rows to draw: the column,
+    /// the terminal row and the text, scrolled by `preview_scroll`. Colors
+    /// of the preview are kept as escape sequences if `colors` is set.
+    pub(crate) fn preview_frame(&self, colors: bool) -> Vec<(usize, u16, String)> {
+        if !self.preview_visible() || self.preview_text_height() == 0 {
+            return Vec::new();
+        }
+        let top = self.preview_top();
+        let lines = self.preview_lines.iter()
+            .skip(self.preview_scroll)
+            .map(String::as_str)
+            .chain(std::iter::repeat(""))
+            .take(self.preview_text_height());
+        match self.preview_position {
+            PreviewPosition::Right => {
+                let column = self.list_width();
+                let width = self.preview_width().saturating_sub(2);
+                lines.enumerate()
+                    .map(|(offset, line)| {
+                        (column, top + offset as u16, format!("│ {}", fit_line(line, width, colors)))
+                    })
+                    .collect()
+            },
+            PreviewPosition::Bottom => {
+                let border = (0, top, "─".repeat(self.width));
+                std::iter::once(border)
+                    .chain(lines.enumerate().map(|(offset, line)| {
+                        (0, top + 1 + offset as u16, fit_line(line, self.width, colors))
+                    }))
+                    .collect()
+            },
+        }
+    }
+
+    /// Returns whether the terminal cell at `column` and `row` is part of
+    /// the preview pane.
+    pub(crate) fn in_preview(&self, column: u16, row: u16) -> bool {
+        let top = self.preview_top();
+        match self.preview_position {
+            PreviewPosition::Right => {
+                self.preview_width() > 0
+                    && column as usize >= self.list_width()
+                    && (top..top + self.list_height() as u16).contains(&row)
+            },
+            PreviewPosition::Bottom => (top..top + self.preview_height() as u16).contains(&row),
+        }
+    }
+
+    /// Scrolls the preview up or down by `lines`, keeping its last line at
+    /// the bottom of the pane at most. Returns whether it moved.
+    pub(crate) fn scroll_preview(&mut self, up: bool, lines: usize) -> bool {
+        if !self.preview_visible() {
+            return false;
+        }
+        let max = self.preview_lines.len().saturating_sub(self.preview_text_height());
+        let scroll = if up {
+            self.preview_scroll.saturating_sub(lines)
+        } else {
+            (self.preview_scroll + lines).min(max)
+        };
+        let moved = scroll != self.preview_scroll;
+        self.preview_scroll = scroll;
+        moved
+    }
+
+    /// Returns the number of columns available for the list.
+    pub(crate) fn list_width(&self) -> usize {
+        self.width - self.preview_width()
+    }
+
+    /// Restarts the preview when the highlight moved to another item and
+    /// receives the output of a running preview command. Returns whether
+    /// the preview changed.
+    pub(crate) fn update_preview(&mut self) -> bool {
+        let Some(preview) = &self.preview else {
+            return false;
+        };
+        let index = self.selected_match().map(|matched| matched.index);
+        let mut changed = false;
+        if index != self.previewed {
+            self.previewed = index;
+            self.preview_lines.clear();
+            self.preview_scroll = 0;
+            #[cfg(feature = "preview-command")]
+            {
+                self.preview_job = None;
+            }
+            if let Some(index) = index {
+                let item = &self.items[index];
+                match preview {
+                    Preview::Text(text) => {
+                        self.preview_lines = sanitize_preview(&text(item));
+                    },
+                    #[cfg(feature = "preview-command")]
+                    Preview::Command(template) => {
+                        let command = template.replace("{}", &quote(&self.plain_text(&item.to_string())));
+                        match PreviewJob::spawn(&command) {
+                            Ok(job) => self.preview_job = Some(job),
+                            Err(error) => self.preview_lines.push(error.to_string()),
+                        }
+                    },
+                }
+            }
+            changed = true;
+        }
+        #[cfg(feature = "preview-command")]
+        {
+            changed |= self.receive_preview();
+        }
+        changed
+    }
+
+    /// Appends the lines the preview command printed since the last call.
+    #[cfg(feature = "preview-command")]
+    fn receive_preview(&mut self) -> bool {
+        let Some(job) = &self.preview_job else {
+            return false;
+        };
+        let mut changed = false;
+        loop {
+            match job.lines.try_recv() {
+                Ok(Output::Line(line)) => {
+                    self.preview_lines.push(clean_line(&line));
+                    changed = true;
+                    if self.preview_lines.len() >= PREVIEW_MAX_LINES {
+                        self.preview_lines.push(String::from("… output truncated"));
+                        self.preview_job = None;
+                        break;
+                    }
+                },
+                Ok(Output::Binary) => {
+                    self.preview_lines = vec![String::from("binary output")];
+                    self.preview_scroll = 0;
+                    self.preview_job = None;
+                    changed = true;
+                    break;
+                },
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    // Both streams are closed; reap the command.
+                    self.preview_job = None;
+                    break;
+                },
+            }
+        }
+        changed
+    }
+
+    /// Returns whether a preview command is still producing output.
+    pub(crate) fn preview_running(&self) -> bool {
+        #[cfg(feature = "preview-command")]
+        {
+            self.preview_job.is_some()
+        }
+        #[cfg(not(feature = "preview-command"))]
+        {
+            false
+        }
+    }
+
+    /// Stops the preview command once picking ends, and makes the next
+    /// pick start a fresh preview.
+    pub(crate) fn stop_preview(&mut self) {
+        self.previewed = None;
+        self.preview_lines.clear();
+        self.preview_scroll = 0;
+        #[cfg(feature = "preview-command")]
+        {
+            self.preview_job = None;
+        }
+    }
+}
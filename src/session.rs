@@ -0,0 +1,48 @@
+//! Thread-safe access to a running picker.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Updates requested through a [`SessionHandle`] since the last redraw.
+#[derive(Default)]
+pub(crate) struct Updates {
+    pub(crate) prompt_label: Option<String>,
+    pub(crate) status_text: Option<String>,
+}
+
+/// A handle for updating a picker while `pick()` is running, e.g. from a
+/// thread doing background work.
+///
+/// Handles are cheap to clone. Every update triggers a redraw.
+#[derive(Clone, Default)]
+pub struct SessionHandle {
+    updates: Arc<Mutex<Updates>>,
+}
+
+impl SessionHandle {
+    /// Replaces the label shown before the query (`"> "` by default).
+    pub fn set_prompt_label(&self, label: impl Into<String>) {
+        self.lock().prompt_label = Some(label.into());
+    }
+
+    /// Shows `text` at the end of the prompt line, e.g. `"indexing… 40%"`.
+    /// An empty string clears it.
+    pub fn set_status_text(&self, text: impl Into<String>) {
+        self.lock().status_text = Some(text.into());
+    }
+
+    /// Returns whether a handle is held outside the picker.
+    pub(crate) fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.updates) > 1
+    }
+
+    /// Takes the pending updates, leaving none behind.
+    pub(crate) fn take_updates(&self) -> Updates {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Updates> {
+        // Updates are plain values, so a panic while holding the lock cannot
+        // leave them inconsistent.
+        self.updates.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,113 @@
+//! Syntax-highlighted previews of files.
+
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+use crate::{looks_binary, PickerState};
+
+/// Number of lines highlighted at the start of a file, as highlighting is
+/// done each time another item is highlighted.
+const HIGHLIGHT_MAX_LINES: usize = 1000;
+
+/// Theme used unless another one is chosen with [`SyntaxPreview::set_theme`].
+const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+/// Renders files as syntax-highlighted text for the preview pane, with
+/// the syntaxes and themes bundled with syntect.
+///
+/// # Example
+///
+/// ```no_run
+/// use fuzzypicker::{FuzzyPicker, SyntaxPreview};
+///
+/// let mut picker = FuzzyPicker::new(&["src/main.rs", "Cargo.toml"]);
+/// let highlighter = SyntaxPreview::new();
+/// picker.set_preview(move |path| highlighter.highlight_file(path));
+/// let picked = picker.pick();
+/// ```
+pub struct SyntaxPreview {
+    syntaxes: SyntaxSet,
+    themes: ThemeSet,
+    theme: String,
+}
+
+impl SyntaxPreview {
+    /// Loads the bundled syntaxes and themes.
+    pub fn new() -> Self {
+        Self {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            themes: ThemeSet::load_defaults(),
+            theme: String::from(DEFAULT_THEME),
+        }
+    }
+
+    /// Returns the names of the bundled themes.
+    pub fn themes(&self) -> impl Iterator<Item = &str> {
+        self.themes.themes.keys().map(String::as_str)
+    }
+
+    /// Switches to the theme called `name`, e.g. `"InspiredGitHub"` for
+    /// light terminals. Returns `false` and keeps the current theme if
+    /// there is no such theme.
+    pub fn set_theme(&mut self, name: &str) -> bool {
+        if !self.themes.themes.contains_key(name) {
+            return false;
+        }
+        self.theme = String::from(name);
+        true
+    }
+
+    /// Returns the contents of the file at `path`, highlighted according
+    /// to its extension or first line, or the error reading it. Binary
+    /// files are summarized by their size.
+    pub fn highlight_file(&self, path: impl AsRef<Path>) -> String {
+        let path = path.as_ref();
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) => return error.to_string(),
+        };
+        if looks_binary(&bytes) {
+            return format!("binary file, {} bytes", bytes.len());
+        }
+        let text = String::from_utf8_lossy(&bytes);
+        let syntax = self.syntaxes.find_syntax_for_file(path)
+            .ok()
+            .flatten()
+            .or_else(|| self.syntaxes.find_syntax_by_first_line(&text))
+            .unwrap_or_else(|| self.syntaxes.find_syntax_plain_text());
+        let mut highlighter = HighlightLines::new(syntax, self.theme());
+        let mut out = String::new();
+        for line in LinesWithEndings::from(&text).take(HIGHLIGHT_MAX_LINES) {
+            match highlighter.highlight_line(line, &self.syntaxes) {
+                Ok(ranges) => out.push_str(&as_24_bit_terminal_escaped(&ranges, false)),
+                Err(_) => out.push_str(line),
+            }
+        }
+        out
+    }
+
+    fn theme(&self) -> &Theme {
+        &self.themes.themes[&self.theme]
+    }
+}
+
+impl Default for SyntaxPreview {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Display + Clone> PickerState<T> {
+    /// Shows the highlighted item as a syntax-highlighted file in the
+    /// preview pane, for items that are file paths. Use a
+    /// [`SyntaxPreview`] with [`set_preview`](Self::set_preview) to choose
+    /// another theme.
+    pub fn set_syntax_preview(&mut self) {
+        let highlighter = SyntaxPreview::new();
+        self.set_preview(move |path| highlighter.highlight_file(path.to_string()));
+    }
+}
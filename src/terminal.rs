@@ -0,0 +1,708 @@
+//! Driving the picker from the terminal.
+
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::OpenOptions;
+use std::io::{stdout, BufWriter, IsTerminal};
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use std::env;
+use crate::theme::colors_supported;
+use crate::{
+    Backend, ContentStyle, Event, Capabilities, CrosstermBackend, Handled, ItemFormatter,
+    Match, PickError, PickOutcome, PickerState, DEFAULT_OPTS_ENV, DEFAULT_TTY_PATH, FALLBACK_TERMINAL_SIZE, IDLE_POLL_INTERVAL, MATCH_POLL_INTERVAL, STREAM_POLL_INTERVAL,
+};
+
+/// Prefixes of `TERM` values of terminals known to report mouse events
+/// with the xterm protocol.
+const MOUSE_TERMS: [&str; 14] = [
+    "xterm", "screen", "tmux", "rxvt", "alacritty", "kitty", "foot", "wezterm", "konsole", "vte", "gnome", "putty",
+    "ghostty", "st-",
+];
+
+/// Returns whether the terminal named by `term`, the value of `TERM`, can
+/// capture the mouse. Windows consoles, which set no `TERM`, always can.
+pub(crate) fn mouse_capture_supported(term: Option<&str>) -> bool {
+    match term {
+        Some(term) => term == "st" || MOUSE_TERMS.iter().any(|prefix| term.starts_with(prefix)),
+        None => cfg!(windows),
+    }
+}
+
+/// Whether `pick()` captures the mouse, see [`FuzzyPicker::set_mouse_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MouseMode {
+    /// Captures the mouse when drawing on a terminal whose `TERM` is known
+    /// to support it, e.g. `xterm-256color` or `tmux`.
+    #[default]
+    Auto,
+    /// Captures the mouse whenever the backend supports it.
+    Always,
+    /// Leaves the mouse to the terminal, e.g. for selecting text.
+    Never,
+}
+
+/// What `pick()` does when stdout is not a terminal, e.g. because it is
+/// redirected to a file or pipe, and no terminal was chosen with
+/// [`FuzzyPicker::set_tty_path`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TtyFallback {
+    /// Draws on the controlling terminal, [`DEFAULT_TTY_PATH`], failing
+    /// with [`PickError::NotATty`] if there is none.
+    #[default]
+    OpenTty,
+    /// Fails with [`PickError::NotATty`].
+    Error,
+    /// Writes to stdout anyway.
+    Stdout,
+}
+
+/// Struct representing a fuzzy picker for interactive item selection.
+///
+/// A thin terminal frontend driving a [`PickerState`], which it derefs
+/// to for all settings that do not concern the terminal.
+pub struct FuzzyPicker<T: Display + Clone> {
+    state: PickerState<T>,
+    backend: Box<dyn Backend>,
+    tty_path: Option<PathBuf>,
+    tty_fallback: TtyFallback,
+    accept_summary: Option<ItemFormatter<T>>,
+    capabilities: Capabilities,
+    mouse_mode: MouseMode,
+    draws_on_terminal: bool,
+    terminal_active: bool,
+    timeout: Option<Duration>,
+    accept_on_timeout: bool,
+    tick_rate: Option<Duration>,
+}
+
+impl<T: Display + Clone> Deref for FuzzyPicker<T> {
+    type Target = PickerState<T>;
+
+    fn deref(&self) -> &PickerState<T> {
+        &self.state
+    }
+}
+
+impl<T: Display + Clone> DerefMut for FuzzyPicker<T> {
+    fn deref_mut(&mut self) -> &mut PickerState<T> {
+        &mut self.state
+    }
+}
+
+impl<T: Display + Clone> FuzzyPicker<T> {
+    /// Constructs a new `FuzzyPicker` instance with the given list of items.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - A slice of items implementing `Display + Clone`.
+    ///
+    /// # Returns
+    ///
+    /// A new `FuzzyPicker` instance.
+    pub fn new(items: &[T]) -> Self {
+        Self::from_items(items.iter().cloned())
+    }
+
+    /// Constructs a new `FuzzyPicker` that takes ownership of `items`,
+    /// which can come from any iterator, e.g. `lines()` or a `map` adapter.
+    ///
+    /// Options set by the user in [`DEFAULT_OPTS_ENV`] are applied first.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// let mut picker = FuzzyPicker::from_items((1..=100).map(|n| format!("item {n}")));
+    /// let picked = picker.pick();
+    /// ```
+    pub fn from_items(items: impl IntoIterator<Item = T>) -> Self {
+        let mut backend = CrosstermBackend::new();
+        let (w, h) = backend.size().unwrap_or(FALLBACK_TERMINAL_SIZE);
+        let mut state = PickerState::from_items(items);
+        state.resize(w, h);
+        let mut picker = Self {
+            state,
+            backend: Box::new(backend),
+            tty_path: None,
+            tty_fallback: TtyFallback::default(),
+            accept_summary: None,
+            capabilities: Capabilities::default(),
+            mouse_mode: MouseMode::default(),
+            draws_on_terminal: false,
+            terminal_active: false,
+            timeout: None,
+            accept_on_timeout: false,
+            tick_rate: None,
+        };
+        if let Ok(opts) = env::var(DEFAULT_OPTS_ENV) {
+            picker.apply_default_opts(&opts, h);
+        }
+        picker
+    }
+
+    /// Enables or disables mouse support.
+    ///
+    /// Mouse capture prevents selecting text with the mouse in some
+    /// terminals and tmux setups, so applications may want to turn it off.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to capture mouse events where supported, i.e.
+    ///   `MouseMode::Auto`, or never. Defaults to `true`.
+    pub fn enable_mouse(&mut self, enabled: bool) {
+        self.mouse_mode = if enabled { MouseMode::Auto } else { MouseMode::Never };
+    }
+
+    /// Chooses when the mouse is captured.
+    ///
+    /// By default it is captured when the picker draws on a terminal whose
+    /// `TERM` is known to report mouse events. `MouseMode::Always` skips the
+    /// detection, e.g. for terminals missing from the list or backends not
+    /// drawing on a terminal. Whether capture was enabled is reported by
+    /// [`capabilities`](Self::capabilities); without it, the picker is used
+    /// with the keyboard alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - Defaults to `MouseMode::Auto`.
+    pub fn set_mouse_mode(&mut self, mode: MouseMode) {
+        self.mouse_mode = mode;
+    }
+
+    /// Replaces the picker with a single summary line on accept, when
+    /// rendering inline, so the scrollback shows what was picked instead
+    /// of leftover UI.
+    ///
+    /// # Arguments
+    ///
+    /// * `summary` - Produces the line to print for the accepted item,
+    ///   e.g. `|branch| format!("✔ branch: {branch}")`.
+    pub fn set_accept_summary(&mut self, summary: impl Fn(&T) -> String + 'static) {
+        self.accept_summary = Some(Box::new(summary));
+    }
+
+    /// Ends picking with `PickOutcome::TimedOut` once nothing was pressed
+    /// for `timeout`, so a picker in a scripted environment cannot block
+    /// forever. Every key press or mouse event restarts the timer.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - How long to wait for input.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Accepts the highlighted item when the timeout set with
+    /// [`set_timeout`](Self::set_timeout) expires, instead of returning no
+    /// item. `pick()` then returns the item as if it had been picked, while
+    /// `pick_outcome()` still reports `PickOutcome::TimedOut`.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to accept on timeout. Defaults to `false`.
+    pub fn set_accept_on_timeout(&mut self, enabled: bool) {
+        self.accept_on_timeout = enabled;
+    }
+
+    /// Sets how often the picker wakes up without input to show streamed
+    /// items, preview output and spinner frames. Shorter intervals make
+    /// these appear sooner at the cost of more CPU time; the screen is
+    /// only redrawn when something changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `tick_rate` - How long to wait for input. Defaults to 50 ms while
+    ///   items or preview output are streaming in and 500 ms otherwise.
+    pub fn set_tick_rate(&mut self, tick_rate: Duration) {
+        self.tick_rate = Some(tick_rate);
+    }
+
+    /// Renders the picker to the terminal at `path` instead of stdout.
+    ///
+    /// Use [`DEFAULT_TTY_PATH`] to draw on the controlling terminal even
+    /// when stdout is redirected, e.g. inside pipelines or git hooks.
+    /// Key and mouse events are always read from the controlling terminal.
+    /// The terminal is handed to the backend with [`Backend::set_output`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The terminal device to open for output when picking.
+    pub fn set_tty_path(&mut self, path: impl Into<PathBuf>) {
+        self.tty_path = Some(path.into());
+    }
+
+    /// Chooses what `pick()` does when stdout is not a terminal, so escape
+    /// sequences do not end up in redirected output.
+    ///
+    /// # Arguments
+    ///
+    /// * `fallback` - Defaults to `TtyFallback::OpenTty`.
+    pub fn set_tty_fallback(&mut self, fallback: TtyFallback) {
+        self.tty_fallback = fallback;
+    }
+
+    /// Replaces the crossterm backend used for events, terminal modes and
+    /// drawing, e.g. to bridge another terminal library or to feed scripted
+    /// events in tests.
+    ///
+    /// As such backends often run without a terminal, this also sets
+    /// [`set_tty_fallback`](Self::set_tty_fallback) to `TtyFallback::Stdout`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::VecDeque;
+    /// use std::io;
+    /// use std::time::Duration;
+    /// use fuzzypicker::{Backend, ContentStyle, Event, FuzzyPicker, KeyCode};
+    ///
+    /// /// Types `ba` and presses Enter.
+    /// struct Scripted(VecDeque<Event>);
+    ///
+    /// impl Backend for Scripted {
+    ///     fn size(&mut self) -> io::Result<(u16, u16)> { Ok((40, 10)) }
+    ///     fn enable_raw_mode(&mut self) -> io::Result<()> { Ok(()) }
+    ///     fn disable_raw_mode(&mut self) -> io::Result<()> { Ok(()) }
+    ///     fn cursor_position(&mut self) -> io::Result<(u16, u16)> { Ok((0, 0)) }
+    ///     fn poll(&mut self, _: Duration) -> io::Result<bool> { Ok(!self.0.is_empty()) }
+    ///     fn read(&mut self) -> io::Result<Event> {
+    ///         self.0.pop_front().ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+    ///     }
+    ///     fn enter_alternate_screen(&mut self) -> io::Result<()> { Ok(()) }
+    ///     fn leave_alternate_screen(&mut self) -> io::Result<()> { Ok(()) }
+    ///     fn move_cursor(&mut self, _: u16, _: u16) -> io::Result<()> { Ok(()) }
+    ///     fn clear_line(&mut self) -> io::Result<()> { Ok(()) }
+    ///     fn print(&mut self, _: &str, _: ContentStyle) -> io::Result<()> { Ok(()) }
+    ///     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// }
+    ///
+    /// let keys = [KeyCode::Char('b'), KeyCode::Char('a'), KeyCode::Enter];
+    /// let mut picker = FuzzyPicker::new(&["apple", "banana"]);
+    /// picker.set_backend(Scripted(keys.into_iter().map(|code| Event::Key(code.into())).collect()));
+    /// assert_eq!(picker.pick().unwrap(), Some("banana"));
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - The backend to use when picking.
+    pub fn set_backend(&mut self, backend: impl Backend + 'static) {
+        self.backend = Box::new(backend);
+        self.tty_fallback = TtyFallback::Stdout;
+    }
+
+    /// Returns the terminal features detected by the last call to `pick()`.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Like `pick()`, but lets the user mark several items with
+    /// `Shift-Tab` or `Ctrl-Space` and returns all of them on accept.
+    ///
+    /// `Ctrl-A` marks every matching item, `Ctrl-D` clears the marks and
+    /// `Ctrl-T` inverts them. Marks are kept while the query changes, also
+    /// for items it filters out, and the number of marked items shown next
+    /// to the match count always includes them.
+    ///
+    /// # Returns
+    ///
+    /// The marked items in their original order, or the highlighted item
+    /// if none are marked. Empty if the selection is cancelled.
+    pub fn pick_multi(&mut self) -> Result<Vec<T>, Box<dyn Error>> {
+        self.state.multi_select = true;
+        self.state.marked.clear();
+        let outcome = self.pick_outcome();
+        self.state.multi_select = false;
+        let Some(item) = outcome?.into_item() else {
+            self.state.marked.clear();
+            return Ok(Vec::new());
+        };
+        if self.state.marked.is_empty() {
+            return Ok(vec![item]);
+        }
+        let mut marked: Vec<usize> = self.state.marked.drain().collect();
+        marked.sort_unstable();
+        Ok(marked.into_iter().map(|index| self.state.items[index].clone()).collect())
+    }
+
+    /// Initiates the interactive item selection process.
+    ///
+    /// Handles keyboard and mouse events to perform fuzzy search, selection,
+    /// and navigation within the item list.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(selected_item))` if an item is selected,
+    /// `Ok(None)` if selection is cancelled,
+    /// `Err(Box<dyn Error>)` for any error encountered during selection.
+    /// Use [`pick_outcome`](Self::pick_outcome) to also learn how the
+    /// selection ended.
+    pub fn pick(&mut self) -> Result<Option<T>, Box<dyn Error>> {
+        Ok(self.pick_outcome()?.into_item())
+    }
+
+    /// Like `pick()`, but also reports which key accepted the item, so one
+    /// picker can offer several actions, like fzf's `--expect`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::{Action, FuzzyPicker, Key, PickOutcome};
+    ///
+    /// let mut picker = FuzzyPicker::new(&["notes.txt", "todo.md"]);
+    /// picker.bind(Key::ctrl('d'), Action::Accept);
+    /// match picker.pick_outcome() {
+    ///     Ok(PickOutcome::Picked { item, key }) if key == Key::ctrl('d') => println!("delete {item}"),
+    ///     Ok(PickOutcome::Picked { item, .. }) => println!("open {item}"),
+    ///     Ok(_) => {},
+    ///     Err(error) => eprintln!("{error}"),
+    /// }
+    /// ```
+    pub fn pick_outcome(&mut self) -> Result<PickOutcome<T>, Box<dyn Error>> {
+        if self.state.exits_empty() {
+            // Nothing to pick from, so leave the terminal untouched.
+            return Ok(PickOutcome::Empty);
+        }
+        self.draws_on_terminal = stdout().is_terminal();
+        if let Some(path) = &self.tty_path {
+            let tty = OpenOptions::new().write(true).open(path)?;
+            self.draws_on_terminal = tty.is_terminal();
+            self.backend.set_output(Box::new(BufWriter::new(tty)));
+        } else if !self.draws_on_terminal {
+            match self.tty_fallback {
+                TtyFallback::OpenTty => {
+                    let tty = OpenOptions::new().write(true).open(DEFAULT_TTY_PATH)
+                        .map_err(|_| PickError::NotATty)?;
+                    self.draws_on_terminal = tty.is_terminal();
+                    self.backend.set_output(Box::new(BufWriter::new(tty)));
+                },
+                TtyFallback::Error => return Err(Box::new(PickError::NotATty)),
+                TtyFallback::Stdout => {},
+            }
+        }
+        self.state.load_saved_state();
+        self.backend.enable_raw_mode()?;
+        self.terminal_active = true;
+        let result = self.event_loop();
+        self.state.background_matching = false;
+        let restored = self.restore_terminal(result.as_ref().ok().and_then(PickOutcome::item));
+        let outcome = result?;
+        restored?;
+        Ok(outcome)
+    }
+
+    /// Runs the interactive session until an item is accepted or the
+    /// selection is cancelled. Expects raw mode to be enabled.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn event_loop(&mut self) -> Result<PickOutcome<T>, Box<dyn Error>> {
+        self.state.started = Instant::now();
+        // The terminal may have been resized since the picker was created.
+        if let Ok((width, height)) = self.backend.size() {
+            self.state.resize(width, height);
+        }
+        self.capabilities.colors = colors_supported();
+        self.state.announced = None;
+        self.state.auto_offered = None;
+        self.enter_screen()?;
+        self.state.invalidate_item_texts();
+        self.render_first_frame()?;
+        self.enable_mouse_capture();
+        self.enable_bracketed_paste();
+        // Neither drawing nor typing waits for the items to be matched,
+        // starting with the first match.
+        self.state.background_matching = true;
+        self.state.start_background_match();
+        let mut needs_render = true;
+        let mut last_input = Instant::now();
+        let outcome = loop {
+            let backend = &mut self.backend;
+            let mut changed = self.state.update(&mut || backend.poll(Duration::ZERO).unwrap_or(false));
+            if self.state.exits_empty() {
+                break PickOutcome::Empty;
+            }
+            match self.state.accept_single_match() {
+                Handled::Redraw => changed = true,
+                Handled::Unchanged | Handled::Suspend | Handled::RunExternal(_) => {},
+                Handled::Done(outcome) => break outcome,
+            }
+            if changed || self.state.spinner() != self.state.drawn_spinner {
+                needs_render = true;
+            }
+            if needs_render {
+                self.render_frame()?;
+                self.state.announce_highlight();
+                needs_render = false;
+            }
+            let mut timeout = match self.tick_rate {
+                _ if self.state.match_job.is_some() => MATCH_POLL_INTERVAL,
+                Some(tick_rate) => tick_rate,
+                None if self.state.item_source.is_some() || self.state.session.is_shared() || self.state.preview_running() => {
+                    STREAM_POLL_INTERVAL
+                },
+                None => IDLE_POLL_INTERVAL,
+            };
+            if let Some(remaining) = self.state.time_until_reload() {
+                timeout = timeout.min(remaining);
+            }
+            if let Some(edited) = self.state.pending_filter {
+                timeout = timeout.min(self.state.debounce.saturating_sub(edited.elapsed()));
+            }
+            if let Some(limit) = self.timeout {
+                let idle = last_input.elapsed();
+                if idle >= limit {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(?limit, "timed out");
+                    break self.timed_out();
+                }
+                timeout = timeout.min(limit - idle);
+            }
+            if !self.backend.poll(timeout)? {
+                continue;
+            }
+            let event = self.backend.read()?;
+            if let Event::Key(_) | Event::Mouse(_) | Event::Paste(_) = event {
+                last_input = Instant::now();
+            }
+            match self.state.process_event(event) {
+                Handled::Redraw => needs_render = true,
+                Handled::Unchanged => {},
+                Handled::Suspend => {
+                    self.suspend()?;
+                    needs_render = true;
+                },
+                Handled::RunExternal(key) => {
+                    self.leave_temporarily()?;
+                    self.state.run_external_command(&key);
+                    self.come_back()?;
+                    needs_render = true;
+                },
+                Handled::Done(outcome) => break outcome,
+            }
+        };
+        self.state.stop_preview();
+        Ok(outcome)
+    }
+
+    /// Returns the outcome of a pick that was left alone for too long.
+    fn timed_out(&mut self) -> PickOutcome<T> {
+        let item = if self.accept_on_timeout {
+            self.state.apply_pending_filter();
+            self.state.selected_item()
+        } else {
+            None
+        };
+        PickOutcome::TimedOut { item }
+    }
+
+    /// Leaves the picker's screen and disables raw mode, if `pick()` left
+    /// them active.
+    fn restore_terminal(&mut self, picked_item: Option<&T>) -> Result<(), Box<dyn Error>> {
+        if !self.terminal_active {
+            return Ok(());
+        }
+        self.terminal_active = false;
+        let left = self.leave_screen(picked_item);
+        self.backend.disable_raw_mode()?;
+        left
+    }
+
+    /// Switches to the alternate screen, or reserves `inline_height` rows
+    /// below the cursor when rendering inline. Mouse capture is enabled
+    /// separately, after the first frame is drawn.
+    fn enter_screen(&mut self) -> Result<(), Box<dyn Error>> {
+        match self.state.inline_height {
+            Some(rows) => {
+                let (_, terminal_height) = self.backend.size()?;
+                let rows = rows.clamp(1, terminal_height.max(1));
+                // Scroll the terminal if needed so the rows below the cursor exist.
+                for _ in 1..rows {
+                    self.backend.print("\n", ContentStyle::new())?;
+                }
+                self.backend.flush()?;
+                let cursor_row = if stdout().is_terminal() {
+                    self.backend.cursor_position()?.1
+                } else {
+                    // Without a way to query the cursor, pin the picker to the bottom.
+                    self.backend.move_cursor(0, terminal_height.saturating_sub(1))?;
+                    for _ in 0..rows {
+                        self.backend.print("\n", ContentStyle::new())?;
+                    }
+                    terminal_height.saturating_sub(1)
+                };
+                // Some Windows consoles report the cursor above the rows just
+                // printed, so never place the picker above the first row.
+                self.state.top = (cursor_row + 1).saturating_sub(rows);
+                self.state.height = rows as usize;
+            },
+            None => {
+                self.state.top = 0;
+                self.backend.enter_alternate_screen()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws the head of the item list before the full list is matched, so
+    /// something appears on screen immediately even for huge item sets.
+    fn render_first_frame(&mut self) -> Result<(), Box<dyn Error>> {
+        if !self.state.prompt.is_empty() {
+            return Ok(());
+        }
+        // With an empty query every item matches, in its original order.
+        let count = self.state.items.len().min(self.state.height);
+        self.state.cache_item_texts(count);
+        let matches = (0..count)
+            .filter(|&index| !self.state.item_texts[index].duplicate)
+            .map(|index| Match { index, header: None })
+            .collect();
+        self.state.set_matches(matches);
+        self.state.num_of_displayable_items = self.state.num_of_items.min(self.state.list_height());
+        self.state.clamp_viewport();
+        if let Some(position) = self.state.initial_selection_position() {
+            self.state.selected = position;
+            self.state.scroll_to_selected();
+        }
+        self.render_frame()
+    }
+
+    fn enable_mouse_capture(&mut self) {
+        let wanted = match self.mouse_mode {
+            MouseMode::Auto => self.draws_on_terminal && mouse_capture_supported(env::var("TERM").ok().as_deref()),
+            MouseMode::Always => true,
+            MouseMode::Never => false,
+        };
+        self.capabilities.mouse_capture = wanted && self.backend.enable_mouse_capture().is_ok();
+    }
+
+    fn enable_bracketed_paste(&mut self) {
+        self.capabilities.bracketed_paste = self.backend.enable_bracketed_paste().is_ok();
+    }
+
+    /// Hands the terminal back to the shell and stops the process with
+    /// `SIGTSTP`, as `Ctrl-Z` does outside of raw mode. Once the process
+    /// is continued, e.g. with `fg`, the picker's screen is set up again.
+    #[cfg(unix)]
+    fn suspend(&mut self) -> Result<(), Box<dyn Error>> {
+        self.leave_temporarily()?;
+        // SAFETY: raise() has no preconditions; it returns once SIGCONT
+        // resumes the process.
+        unsafe {
+            libc::raise(libc::SIGTSTP);
+        }
+        self.come_back()
+    }
+
+    /// Suspending is only supported on Unix.
+    #[cfg(not(unix))]
+    fn suspend(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Restores the terminal for another program while picking, keeping the
+    /// picker's state.
+    fn leave_temporarily(&mut self) -> Result<(), Box<dyn Error>> {
+        self.leave_screen(None)?;
+        self.backend.disable_raw_mode()?;
+        Ok(())
+    }
+
+    /// Sets up the picker's screen again after
+    /// [`leave_temporarily`](Self::leave_temporarily).
+    fn come_back(&mut self) -> Result<(), Box<dyn Error>> {
+        self.backend.enable_raw_mode()?;
+        // The terminal may have been resized in the meantime.
+        if let Ok((width, height)) = self.backend.size() {
+            self.state.resize(width, height);
+        }
+        self.enter_screen()?;
+        self.state.relayout();
+        self.enable_mouse_capture();
+        self.enable_bracketed_paste();
+        Ok(())
+    }
+
+    /// Restores the screen. When rendering inline, the picker's rows are
+    /// erased and replaced by the accept summary, if one is set.
+    fn leave_screen(&mut self, picked_item: Option<&T>) -> Result<(), Box<dyn Error>> {
+        if self.capabilities.mouse_capture {
+            self.backend.disable_mouse_capture()?;
+        }
+        if self.capabilities.bracketed_paste {
+            self.backend.disable_bracketed_paste()?;
+        }
+        if self.state.inline_height.is_some() {
+            self.clear_rows()?;
+            self.backend.move_cursor(0, self.state.top)?;
+            if let (Some(item), Some(summary)) = (picked_item, &self.accept_summary) {
+                self.backend.print(&format!("{}\r\n", summary(item)), ContentStyle::new())?;
+            }
+        } else {
+            self.backend.leave_alternate_screen()?;
+        }
+        self.backend.show_cursor()?;
+        self.backend.flush()?;
+        Ok(())
+    }
+
+    fn clear_rows(&mut self) -> Result<(), Box<dyn Error>> {
+        for row in 0..self.state.height as u16 {
+            self.backend.move_cursor(0, self.state.top + row)?;
+            self.backend.clear_line()?;
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn render_frame(&mut self) -> Result<(), Box<dyn Error>> {
+        let frame = self.state.frame(self.capabilities.colors);
+        self.backend.hide_cursor()?;
+        for row in frame.rows {
+            self.backend.move_cursor(0, row)?;
+            self.backend.clear_line()?;
+        }
+        for span in frame.spans {
+            self.backend.move_cursor(span.column as u16, span.row)?;
+            self.backend.print(&span.text, span.style)?;
+        }
+        let (column, row) = frame.cursor;
+        self.backend.move_cursor(column as u16, row)?;
+        self.backend.show_cursor()?;
+        self.backend.flush()?;
+        Ok(())
+    }
+}
+
+impl<T: Display + Clone> Drop for FuzzyPicker<T> {
+    /// Restores the terminal if the picker is dropped while it still
+    /// holds it, e.g. when unwinding out of `pick()`.
+    fn drop(&mut self) {
+        let _ = self.restore_terminal(None);
+    }
+}
+
+impl FuzzyPicker<String> {
+    /// Constructs a picker over the lines of stdin, like fzf, for use at
+    /// the end of a `producer | my-tool` pipeline.
+    ///
+    /// Lines are read on a background thread and appear as they arrive.
+    /// The picker is drawn on [`DEFAULT_TTY_PATH`] so stdout stays free
+    /// for printing the selection.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::FuzzyPicker;
+    ///
+    /// if let Ok(Some(line)) = FuzzyPicker::from_stdin().pick() {
+    ///     println!("{}", line);
+    /// }
+    /// ```
+    pub fn from_stdin() -> Self {
+        let mut picker = Self::new(&[]);
+        picker.read_items(std::io::stdin());
+        picker.set_tty_path(DEFAULT_TTY_PATH);
+        picker
+    }
+}
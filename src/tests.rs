@@ -0,0 +1,770 @@
+//! Tests driving a headless `PickerState`, or a `FuzzyPicker` through a
+//! recording backend.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::time::Duration;
+use crossterm::event::MouseEventKind;
+use crate::{
+    Action, Backend, Column, ContentStyle, Event, FuzzyPicker, Key, KeyCode, KeyEvent, KeyModifiers, Layout, MatchMode, MouseEvent, MouseMode,
+    PickOutcome, PickerState, RankingMode,
+};
+
+/// Returns the text of the rows shown by `state`.
+fn rows<T: Display + Clone>(state: &mut PickerState<T>) -> Vec<String> {
+    state.visible_rows().into_iter().map(|row| row.text).collect()
+}
+
+/// Types `query` into the prompt.
+fn type_query<T: Display + Clone>(state: &mut PickerState<T>, query: &str) {
+    for ch in query.chars() {
+        state.handle_key(KeyCode::Char(ch).into());
+    }
+}
+
+/// Returns the text of the highlighted row, if any.
+fn highlighted<T: Display + Clone>(state: &mut PickerState<T>) -> Option<String> {
+    state.visible_rows().into_iter().find(|row| row.selected).map(|row| row.text)
+}
+
+/// Returns a state over the numbers 1 to `count`, showing five items.
+fn numbers(count: usize) -> PickerState<String> {
+    let mut state = PickerState::from_items((1..=count).map(|n| n.to_string()));
+    state.resize(40, 6);
+    state
+}
+
+/// A backend replaying key presses and recording the calls made to it.
+/// Reading past the last key press fails.
+struct Recording {
+    keys: VecDeque<Event>,
+    calls: Rc<RefCell<Vec<String>>>,
+}
+
+impl Recording {
+    /// Returns a backend pressing `keys`, and the calls it records.
+    fn new(keys: impl IntoIterator<Item = KeyCode>) -> (Self, Rc<RefCell<Vec<String>>>) {
+        let calls = Rc::default();
+        let keys = keys.into_iter().map(|code| Event::Key(code.into())).collect();
+        (Self { keys, calls: Rc::clone(&calls) }, calls)
+    }
+
+    fn record(&mut self, call: impl Into<String>) -> io::Result<()> {
+        self.calls.borrow_mut().push(call.into());
+        Ok(())
+    }
+}
+
+impl Backend for Recording {
+    fn size(&mut self) -> io::Result<(u16, u16)> {
+        Ok((40, 10))
+    }
+
+    fn enable_raw_mode(&mut self) -> io::Result<()> {
+        self.record("enable_raw_mode")
+    }
+
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        self.record("disable_raw_mode")
+    }
+
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+        Ok((0, 0))
+    }
+
+    fn poll(&mut self, _: Duration) -> io::Result<bool> {
+        Ok(true)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        self.keys.pop_front().ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+    }
+
+    fn enter_alternate_screen(&mut self) -> io::Result<()> {
+        self.record("enter_alternate_screen")
+    }
+
+    fn leave_alternate_screen(&mut self) -> io::Result<()> {
+        self.record("leave_alternate_screen")
+    }
+
+    fn enable_mouse_capture(&mut self) -> io::Result<()> {
+        self.record("enable_mouse_capture")
+    }
+
+    fn disable_mouse_capture(&mut self) -> io::Result<()> {
+        self.record("disable_mouse_capture")
+    }
+
+    fn move_cursor(&mut self, _: u16, _: u16) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn clear_line(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn print(&mut self, text: &str, _: ContentStyle) -> io::Result<()> {
+        self.record(format!("print {}", text))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns how often `call` was recorded.
+fn count(calls: &Rc<RefCell<Vec<String>>>, call: &str) -> usize {
+    calls.borrow().iter().filter(|recorded| *recorded == call).count()
+}
+
+/// Presses `code` `times` times.
+fn press<T: Display + Clone>(state: &mut PickerState<T>, code: KeyCode, times: usize) {
+    for _ in 0..times {
+        state.handle_key(code.into());
+    }
+}
+
+#[test]
+fn set_items_iter_rematches_a_started_state() {
+    let mut state = PickerState::new(&["apple", "banana", "cherry"]);
+    state.handle_key(KeyCode::Down.into());
+    assert_eq!(rows(&mut state), ["apple", "banana", "cherry"]);
+    state.set_items_iter(["x"]);
+    assert_eq!(rows(&mut state), ["x"]);
+    assert!(state.visible_rows()[0].selected);
+}
+
+#[test]
+fn set_items_iter_keeps_the_query() {
+    let mut state = PickerState::new(&["apple", "banana"]);
+    type_query(&mut state, "an");
+    assert_eq!(rows(&mut state), ["banana"]);
+    state.set_items_iter(["mango", "kiwi", "orange"]);
+    assert_eq!(rows(&mut state), ["mango", "orange"]);
+}
+
+#[test]
+fn streamed_items_are_filtered_by_chips() {
+    let (sender, receiver) = mpsc::channel();
+    let mut state = PickerState::new(&["apple", "banana"]);
+    state.set_item_source(receiver);
+    type_query(&mut state, "ap");
+    state.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT));
+    assert_eq!(rows(&mut state), ["apple"]);
+    sender.send("zzz").unwrap();
+    sender.send("grape").unwrap();
+    assert_eq!(rows(&mut state), ["apple", "grape"]);
+}
+
+#[test]
+fn streamed_items_are_not_added_to_narrowed_items() {
+    let (sender, receiver) = mpsc::channel();
+    let mut state = PickerState::new(&["apple", "banana"]);
+    state.set_item_source(receiver);
+    type_query(&mut state, "p");
+    state.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::ALT));
+    assert_eq!(rows(&mut state), ["apple"]);
+    sender.send("zzz").unwrap();
+    sender.send("pear").unwrap();
+    assert_eq!(rows(&mut state), ["apple"]);
+    state.handle_key(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::ALT));
+    assert_eq!(rows(&mut state), ["apple", "banana", "zzz", "pear"]);
+}
+
+#[test]
+fn navigating_without_matches_does_nothing() {
+    let mut state = PickerState::new(&["apple", "banana"]);
+    type_query(&mut state, "zz");
+    assert!(rows(&mut state).is_empty());
+    let keys = [
+        KeyEvent::from(KeyCode::Up),
+        KeyEvent::from(KeyCode::Down),
+        KeyEvent::new(KeyCode::Home, KeyModifiers::CONTROL),
+        KeyEvent::new(KeyCode::End, KeyModifiers::CONTROL),
+        KeyEvent::new(KeyCode::Char('1'), KeyModifiers::ALT),
+        KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL),
+        KeyEvent::from(KeyCode::Tab),
+    ];
+    for key in keys {
+        assert_eq!(state.handle_key(key), None);
+    }
+    assert!(rows(&mut state).is_empty());
+    state.handle_key(KeyCode::Backspace.into());
+    state.handle_key(KeyCode::Backspace.into());
+    let visible = state.visible_rows();
+    assert_eq!(visible.len(), 2);
+    assert!(visible[0].selected);
+}
+
+#[test]
+fn accepting_without_matches_keeps_picking() {
+    let mut state = PickerState::new(&["apple", "banana"]);
+    type_query(&mut state, "zz");
+    assert_eq!(state.handle_key(KeyCode::Enter.into()), None);
+    assert_eq!(state.handle_key(KeyEvent::new(KeyCode::Char('1'), KeyModifiers::ALT)), None);
+    state.set_accept_query(true);
+    assert_eq!(
+        state.handle_key(KeyCode::Enter.into()),
+        Some(PickOutcome::Query { query: String::from("zz"), key: Key::plain(KeyCode::Enter) }),
+    );
+}
+
+#[test]
+fn marking_without_matches_does_nothing() {
+    let mut state = PickerState::new(&["apple", "banana"]);
+    state.multi_select = true;
+    type_query(&mut state, "zz");
+    for key in [
+        KeyEvent::from(KeyCode::BackTab),
+        KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL),
+        KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
+    ] {
+        assert_eq!(state.handle_key(key), None);
+    }
+    assert!(state.marked.is_empty());
+}
+
+#[test]
+fn an_empty_item_list_is_navigable() {
+    let mut state = PickerState::<&str>::new(&[]);
+    state.set_wrap(false);
+    for key in [KeyCode::Up, KeyCode::Down, KeyCode::Enter, KeyCode::Backspace] {
+        assert_eq!(state.handle_key(key.into()), None);
+    }
+    assert!(rows(&mut state).is_empty());
+    assert_eq!(state.handle_key(KeyCode::Esc.into()), Some(PickOutcome::Cancelled { key: Key::plain(KeyCode::Esc) }));
+}
+
+#[test]
+fn fuzzy_matches_rank_the_closest_first() {
+    let mut state = PickerState::new(&["a_b_c", "xyz", "abc"]);
+    type_query(&mut state, "abc");
+    assert_eq!(rows(&mut state), ["abc", "a_b_c"]);
+}
+
+#[test]
+fn exact_mode_matches_substrings_only() {
+    let mut state = PickerState::new(&["a_b_c", "xyz", "abc"]);
+    state.set_match_mode(MatchMode::Exact);
+    type_query(&mut state, "abc");
+    assert_eq!(rows(&mut state), ["abc"]);
+}
+
+#[test]
+fn preserve_order_keeps_the_item_order() {
+    let mut state = PickerState::new(&["cab", "abc", "xabcx"]);
+    state.set_ranking_mode(RankingMode::PreserveOrder);
+    type_query(&mut state, "ab");
+    assert_eq!(rows(&mut state), ["cab", "abc", "xabcx"]);
+}
+
+#[test]
+fn editing_the_query_widens_the_matches_again() {
+    let mut state = PickerState::new(&["apple", "banana", "cherry"]);
+    type_query(&mut state, "ban");
+    assert_eq!(rows(&mut state), ["banana"]);
+    state.handle_key(KeyCode::Backspace.into());
+    state.handle_key(KeyCode::Backspace.into());
+    assert_eq!(rows(&mut state), ["banana"]);
+    state.handle_key(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL));
+    assert_eq!(state.query(), "");
+    assert_eq!(rows(&mut state), ["apple", "banana", "cherry"]);
+}
+
+#[test]
+fn modified_keys_are_not_typed() {
+    let mut state = PickerState::new(&["apple"]);
+    state.handle_key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL));
+    state.handle_key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT));
+    state.handle_key(KeyEvent::new(KeyCode::Char('X'), KeyModifiers::SHIFT));
+    assert_eq!(state.query(), "X");
+}
+
+#[test]
+fn ctrl_alt_keys_can_be_bound() {
+    let mut state = numbers(20);
+    state.bind("ctrl-alt-x".parse().unwrap(), Action::SelectNext);
+    let ctrl_alt_x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL | KeyModifiers::ALT);
+    state.handle_key(ctrl_alt_x);
+    assert_eq!(state.prompt, "");
+    assert_eq!(highlighted(&mut state).as_deref(), Some("2"));
+    state.unbind("ctrl-alt-x".parse().unwrap());
+    state.handle_key(ctrl_alt_x);
+    // Only Windows reports AltGr as Ctrl + Alt.
+    assert_eq!(state.prompt, if cfg!(windows) { "x" } else { "" });
+}
+
+#[test]
+fn navigation_wraps_around_by_default() {
+    let mut state = numbers(3);
+    press(&mut state, KeyCode::Up, 1);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("3"));
+    press(&mut state, KeyCode::Down, 1);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("1"));
+}
+
+#[test]
+fn navigation_stops_at_the_ends_without_wrap() {
+    let mut state = numbers(3);
+    state.set_wrap(false);
+    press(&mut state, KeyCode::Up, 1);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("1"));
+    press(&mut state, KeyCode::Down, 5);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("3"));
+}
+
+#[test]
+fn navigation_skips_group_headers() {
+    let mut state = numbers(4);
+    state.set_group_by(|n: &String| if n.parse::<u32>().unwrap() <= 2 { "low".into() } else { "high".into() });
+    assert_eq!(rows(&mut state), ["low", "1", "2", "high", "3"]);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("1"));
+    press(&mut state, KeyCode::Down, 2);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("3"));
+    state.set_wrap(false);
+    press(&mut state, KeyCode::Up, 5);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("1"));
+}
+
+#[test]
+fn jumps_go_to_the_first_last_and_nth_items() {
+    let mut state = numbers(20);
+    state.handle_key(KeyEvent::new(KeyCode::End, KeyModifiers::CONTROL));
+    assert_eq!(highlighted(&mut state).as_deref(), Some("20"));
+    assert_eq!(rows(&mut state), ["16", "17", "18", "19", "20"]);
+    state.handle_key(KeyEvent::new(KeyCode::Char('2'), KeyModifiers::ALT));
+    assert_eq!(highlighted(&mut state).as_deref(), Some("17"));
+    state.handle_key(KeyEvent::new(KeyCode::Home, KeyModifiers::CONTROL));
+    assert_eq!(highlighted(&mut state).as_deref(), Some("1"));
+    assert_eq!(rows(&mut state), ["1", "2", "3", "4", "5"]);
+}
+
+#[test]
+fn scrolling_follows_the_highlight() {
+    let mut state = numbers(20);
+    press(&mut state, KeyCode::Down, 5);
+    assert_eq!(rows(&mut state), ["2", "3", "4", "5", "6"]);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("6"));
+}
+
+#[test]
+fn scrolloff_keeps_rows_around_the_highlight() {
+    let mut state = numbers(20);
+    state.set_scrolloff(1);
+    press(&mut state, KeyCode::Down, 3);
+    assert_eq!(rows(&mut state), ["1", "2", "3", "4", "5"]);
+    press(&mut state, KeyCode::Down, 1);
+    assert_eq!(rows(&mut state), ["2", "3", "4", "5", "6"]);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("5"));
+    press(&mut state, KeyCode::Up, 3);
+    assert_eq!(rows(&mut state), ["1", "2", "3", "4", "5"]);
+    state.handle_key(KeyEvent::new(KeyCode::End, KeyModifiers::CONTROL));
+    assert_eq!(rows(&mut state), ["16", "17", "18", "19", "20"]);
+}
+
+#[test]
+fn scrolloff_is_limited_by_the_list_height() {
+    let mut state = numbers(20);
+    state.set_scrolloff(10);
+    press(&mut state, KeyCode::Down, 3);
+    assert_eq!(rows(&mut state), ["2", "3", "4", "5", "6"]);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("4"));
+}
+
+#[test]
+fn marking_toggles_and_moves_on() {
+    let mut state = numbers(3);
+    state.multi_select = true;
+    state.handle_key(KeyCode::BackTab.into());
+    let marked: Vec<bool> = state.visible_rows().iter().map(|row| row.marked).collect();
+    assert_eq!(marked, [true, false, false]);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("2"));
+    state.handle_key(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL));
+    let marked: Vec<bool> = state.visible_rows().iter().map(|row| row.marked).collect();
+    assert_eq!(marked, [false, true, true]);
+    state.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+    assert!(state.marked.is_empty());
+}
+
+#[test]
+fn marking_all_respects_the_query_and_the_limit() {
+    let mut state = PickerState::new(&["apple", "apricot", "banana", "avocado"]);
+    state.multi_select = true;
+    type_query(&mut state, "ap");
+    state.handle_key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL));
+    assert_eq!(state.marked.len(), 2);
+    state.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+    state.set_max_selections(1);
+    state.handle_key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL));
+    assert_eq!(state.marked.len(), 1);
+}
+
+#[test]
+fn marking_needs_multi_select() {
+    let mut state = numbers(3);
+    state.handle_key(KeyCode::BackTab.into());
+    assert!(state.marked.is_empty());
+}
+
+#[test]
+fn accepting_returns_the_highlighted_item_and_key() {
+    let mut state = numbers(3);
+    press(&mut state, KeyCode::Down, 1);
+    assert_eq!(
+        state.handle_key(KeyCode::Enter.into()),
+        Some(PickOutcome::Picked { item: String::from("2"), key: Key::plain(KeyCode::Enter) }),
+    );
+}
+
+#[test]
+fn disabled_items_are_skipped() {
+    let mut state = numbers(3);
+    state.set_disabled_by(|n: &String| n == "2");
+    press(&mut state, KeyCode::Down, 1);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("3"));
+    let disabled: Vec<bool> = state.visible_rows().iter().map(|row| row.disabled).collect();
+    assert_eq!(disabled, [false, true, false]);
+}
+
+#[test]
+fn dedupe_and_max_results_limit_the_rows() {
+    let mut state = PickerState::new(&["a", "b", "a", "c", "b"]);
+    state.set_dedupe(true);
+    assert_eq!(rows(&mut state), ["a", "b", "c"]);
+    state.set_max_results(2);
+    state.handle_key(KeyCode::Char('x').into());
+    state.handle_key(KeyCode::Backspace.into());
+    assert_eq!(rows(&mut state), ["a", "b"]);
+}
+
+#[test]
+fn wheel_scrolling_moves_the_highlight_out_of_the_scrolloff() {
+    let mut state = numbers(20);
+    state.set_scrolloff(1);
+    let wheel = MouseEvent { kind: MouseEventKind::ScrollDown, column: 5, row: 2, modifiers: KeyModifiers::NONE };
+    state.handle_mouse(wheel);
+    assert_eq!(rows(&mut state), ["2", "3", "4", "5", "6"]);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("3"));
+}
+
+#[cfg(feature = "ratatui")]
+#[test]
+fn the_widget_draws_the_terminal_frame() {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+    let mut state = PickerState::new(&["apple", "banana", "cherry"]);
+    state.handle_key(KeyCode::Down.into());
+    let area = Rect::new(0, 0, 30, 5);
+    let mut buffer = Buffer::empty(area);
+    state.widget().render(area, &mut buffer);
+    let frame = state.frame(crate::theme::colors_supported());
+    assert!(!frame.spans.is_empty());
+    for span in frame.spans {
+        let drawn: String = (0..span.text.chars().count())
+            .map(|offset| buffer.get((span.column + offset) as u16, span.row).symbol())
+            .collect();
+        assert_eq!(drawn, span.text);
+    }
+}
+
+#[cfg(feature = "ratatui")]
+#[test]
+fn clicks_are_relative_to_the_widget_area() {
+    use crossterm::event::MouseButton;
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+    let mut state = PickerState::new(&["apple", "banana", "cherry"]);
+    let area = Rect::new(10, 2, 30, 6);
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 40, 8));
+    state.widget().render(area, &mut buffer);
+    let click = |column, row| MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), column, row, modifiers: KeyModifiers::NONE };
+    // Left of the widget, on the row of banana.
+    state.handle_mouse(click(5, 4));
+    assert_eq!(highlighted(&mut state).as_deref(), Some("apple"));
+    state.handle_mouse(click(12, 4));
+    assert_eq!(highlighted(&mut state).as_deref(), Some("banana"));
+}
+
+#[test]
+fn frames_are_drawn_through_the_backend() {
+    let (backend, calls) = Recording::new([KeyCode::Char('b'), KeyCode::Enter]);
+    let mut picker = FuzzyPicker::new(&["apple", "banana"]);
+    picker.set_backend(backend);
+    assert_eq!(picker.pick().unwrap(), Some("banana"));
+    assert!(count(&calls, "print apple") > 0);
+    assert!(count(&calls, "print banana") > 0);
+    assert_eq!(count(&calls, "enter_alternate_screen"), 1);
+    assert_eq!(count(&calls, "leave_alternate_screen"), 1);
+    assert_eq!(calls.borrow().last().map(String::as_str), Some("disable_raw_mode"));
+}
+
+#[test]
+fn an_unreadable_history_file_is_ignored() {
+    let (backend, _) = Recording::new([KeyCode::Char('b'), KeyCode::Enter]);
+    let mut picker = FuzzyPicker::new(&["apple", "banana"]);
+    picker.set_backend(backend);
+    // A directory cannot be read as a history file, nor written as one.
+    picker.set_history_file(std::env::temp_dir());
+    assert_eq!(picker.pick().unwrap(), Some("banana"));
+    let mut state = PickerState::new(&["apple", "banana"]);
+    state.set_history_file(std::env::temp_dir());
+    type_query(&mut state, "b");
+    assert_eq!(highlighted(&mut state).as_deref(), Some("banana"));
+}
+
+#[test]
+fn default_opts_name_layouts_like_fzf() {
+    let mut picker = FuzzyPicker::new(&["apple"]);
+    picker.apply_default_opts("--layout default", 24);
+    assert_eq!(picker.layout, Layout::Reverse);
+    picker.apply_default_opts("--reverse", 24);
+    assert_eq!(picker.layout, Layout::Default);
+    picker.apply_default_opts("--layout=default --layout=reverse", 24);
+    assert_eq!(picker.layout, Layout::Default);
+}
+
+#[test]
+fn inline_pickers_grow_back_when_the_terminal_does() {
+    let mut state = numbers(20);
+    state.set_height(6);
+    state.handle_event(&Event::Resize(40, 4));
+    assert_eq!(state.height, 4);
+    state.handle_event(&Event::Resize(40, 30));
+    assert_eq!(state.height, 6);
+    assert_eq!(rows(&mut state).len(), 5);
+}
+
+#[test]
+fn mouse_capture_is_detected_from_term() {
+    use crate::terminal::mouse_capture_supported;
+    for term in ["xterm-256color", "tmux-256color", "screen", "alacritty", "st", "st-256color"] {
+        assert!(mouse_capture_supported(Some(term)), "{}", term);
+    }
+    for term in ["dumb", "linux", "vt100", "stupid"] {
+        assert!(!mouse_capture_supported(Some(term)), "{}", term);
+    }
+    assert_eq!(mouse_capture_supported(None), cfg!(windows));
+}
+
+#[test]
+fn mouse_mode_overrides_detection() {
+    // The recording backend does not draw on a terminal.
+    for (mode, captured) in [(MouseMode::Auto, false), (MouseMode::Always, true), (MouseMode::Never, false)] {
+        let (backend, calls) = Recording::new([KeyCode::Enter]);
+        let mut picker = FuzzyPicker::new(&["apple"]);
+        picker.set_backend(backend);
+        picker.set_mouse_mode(mode);
+        picker.pick().unwrap();
+        assert_eq!(picker.capabilities().mouse_capture, captured, "{:?}", mode);
+        assert_eq!(count(&calls, "enable_mouse_capture"), usize::from(captured));
+        assert_eq!(count(&calls, "disable_mouse_capture"), usize::from(captured));
+    }
+}
+
+/// Asserts that the terminal was set up and restored exactly once.
+fn assert_restored_once(calls: &Rc<RefCell<Vec<String>>>) {
+    for call in [
+        "enable_raw_mode",
+        "disable_raw_mode",
+        "enter_alternate_screen",
+        "leave_alternate_screen",
+        "enable_mouse_capture",
+        "disable_mouse_capture",
+    ] {
+        assert_eq!(count(calls, call), 1, "{}", call);
+    }
+    assert_eq!(calls.borrow().last().map(String::as_str), Some("disable_raw_mode"));
+}
+
+#[test]
+fn the_terminal_is_restored_once_when_reading_fails() {
+    // Reading past the last key fails, ending `pick()` through `?`.
+    let (backend, calls) = Recording::new([KeyCode::Char('a')]);
+    let mut picker = FuzzyPicker::new(&["apple", "banana"]);
+    picker.set_backend(backend);
+    picker.set_mouse_mode(MouseMode::Always);
+    assert!(picker.pick().is_err());
+    assert_restored_once(&calls);
+    drop(picker);
+    assert_restored_once(&calls);
+}
+
+#[test]
+fn the_terminal_is_restored_once_when_unwinding() {
+    let (backend, calls) = Recording::new([KeyCode::Char('x'), KeyCode::Enter]);
+    let unwound = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut picker = FuzzyPicker::new(&["apple", "banana"]);
+        picker.set_backend(backend);
+        picker.set_mouse_mode(MouseMode::Always);
+        picker.set_footer(|info| {
+            assert!(info.query.is_empty(), "drawing failed");
+            String::new()
+        });
+        picker.pick()
+    }));
+    assert!(unwound.is_err());
+    assert_restored_once(&calls);
+}
+
+/// Returns a state listing processes in the columns ID, NAME and DESC.
+fn table() -> PickerState<&'static str> {
+    let mut state = PickerState::new(&["1 | nginx | web server", "42 | redis | cache"]);
+    state.set_delimiter(" | ");
+    state.set_columns(vec![Column::new("ID"), Column::new("NAME"), Column::new("DESC")]);
+    state
+}
+
+#[test]
+fn column_actions_resize_the_focused_column() {
+    let mut state = table();
+    assert_eq!(rows(&mut state), ["1   nginx  web server", "42  redis  cache"]);
+    state.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::ALT));
+    assert_eq!(rows(&mut state), ["1     nginx  web server", "42    redis  cache"]);
+    state.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::ALT));
+    state.handle_key(KeyEvent::new(KeyCode::Left, KeyModifiers::ALT));
+    assert_eq!(rows(&mut state), ["1     ngi  web server", "42    red  cache"]);
+}
+
+#[test]
+fn column_actions_hide_and_show_columns() {
+    let mut state = table();
+    state.handle_key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::ALT));
+    assert_eq!(rows(&mut state), ["nginx  web server", "redis  cache"]);
+    state.handle_key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::ALT));
+    assert_eq!(rows(&mut state), ["web server", "cache"]);
+    // The last visible column stays.
+    assert!(state.handle_key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::ALT)).is_none());
+    assert_eq!(rows(&mut state), ["web server", "cache"]);
+    state.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::ALT));
+    assert_eq!(rows(&mut state), ["1   nginx  web server", "42  redis  cache"]);
+    // Hidden columns are still searched.
+    state.set_columns(vec![Column::new("ID").hidden(true), Column::new("NAME"), Column::new("DESC")]);
+    type_query(&mut state, "42");
+    assert_eq!(rows(&mut state), ["redis  cache"]);
+}
+
+#[test]
+fn column_layouts_are_restored_from_the_layout_file() {
+    let path = std::env::temp_dir().join(format!("fuzzypicker-columns-{}", std::process::id()));
+    let mut state = table();
+    state.set_column_layout_file(&path);
+    rows(&mut state);
+    state.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::ALT));
+    state.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::ALT));
+    state.handle_key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::ALT));
+    state.handle_key(KeyCode::Enter.into());
+    let mut restored = table();
+    restored.set_column_layout_file(&path);
+    let restored = rows(&mut restored);
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(restored, ["1     web server", "42    cache"]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn configured_columns_apply_to_columns_set_later() {
+    use crate::{ColumnConfig, PickerConfig};
+    let config = PickerConfig {
+        columns: [
+            (String::from("ID"), ColumnConfig { hidden: Some(true), ..ColumnConfig::default() }),
+            (String::from("NAME"), ColumnConfig { width: Some(3), ..ColumnConfig::default() }),
+        ].into(),
+        ..PickerConfig::default()
+    };
+    let mut state = PickerState::new(&["1 | nginx | web server", "42 | redis | cache"]).with_config(config);
+    state.set_delimiter(" | ");
+    state.set_columns(vec![Column::new("ID"), Column::new("NAME"), Column::new("DESC")]);
+    assert_eq!(rows(&mut state), ["ngi  web server", "red  cache"]);
+}
+
+#[cfg(all(target_os = "linux", feature = "preview-command"))]
+#[test]
+fn stopping_a_preview_kills_the_programs_the_shell_started() {
+    let mut state = PickerState::new(&["apple"]);
+    state.set_preview_command("sleep 30 & echo $!; wait");
+    rows(&mut state);
+    while state.preview_lines.is_empty() {
+        state.update(&mut || false);
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let pid = state.preview_lines[0].trim().to_string();
+    state.stop_preview();
+    // Killed processes may linger as zombies until they are reaped.
+    let running = || std::fs::read_to_string(format!("/proc/{}/stat", pid))
+        .is_ok_and(|stat| stat.split_whitespace().nth(2) != Some("Z"));
+    for _ in 0..100 {
+        if !running() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!running());
+}
+
+#[test]
+fn the_ranker_drops_results_of_stale_jobs() {
+    use crate::matching::{Pattern, Ranker};
+    let texts: Vec<std::sync::Arc<str>> = vec!["apple".into(), "kiwi".into()];
+    let mut ranker = Ranker::default();
+    ranker.start(Pattern::new("a", MatchMode::Fuzzy).unwrap(), texts.clone(), false);
+    ranker.start(Pattern::new("k", MatchMode::Fuzzy).unwrap(), texts, false);
+    let scores = loop {
+        match ranker.finished() {
+            Some(scores) => break scores,
+            None => std::thread::sleep(Duration::from_millis(1)),
+        }
+    };
+    assert!(scores[0].is_none());
+    assert!(scores[1].is_some());
+}
+
+#[test]
+fn background_matches_replace_the_matches_once_finished() {
+    let mut state = numbers(30_000);
+    state.background_matching = true;
+    rows(&mut state);
+    type_query(&mut state, "2999");
+    while {
+        state.update(&mut || false);
+        state.match_job.is_some()
+    } {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let mut expected = numbers(30_000);
+    type_query(&mut expected, "2999");
+    assert_eq!(rows(&mut state), rows(&mut expected));
+    assert_eq!(state.num_of_matches, expected.num_of_matches);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("2999"));
+}
+
+#[test]
+fn the_first_background_match_applies_the_initial_selection() {
+    let mut state = numbers(30_000);
+    state.set_initial_selection_by(|item: &String| item == "29999");
+    // Started like `pick()` does, without matching synchronously.
+    state.active = true;
+    state.background_matching = true;
+    state.invalidate_item_texts();
+    state.start_background_match();
+    assert!(state.match_job.is_some());
+    while !state.finish_background_match() {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(state.num_of_matches, 30_000);
+    assert_eq!(highlighted(&mut state).as_deref(), Some("29999"));
+}
+
+#[test]
+fn accepting_during_a_background_match_uses_the_query() {
+    let mut state = PickerState::new(&["apple", "banana"]);
+    state.background_matching = true;
+    rows(&mut state);
+    type_query(&mut state, "ban");
+    state.update(&mut || false);
+    assert!(state.match_job.is_some());
+    let outcome = state.handle_key(KeyCode::Enter.into());
+    assert!(matches!(outcome, Some(PickOutcome::Picked { item: "banana", .. })));
+}
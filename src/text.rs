@@ -0,0 +1,45 @@
+//! Display widths and editing of text containing characters that take up
+//! two terminal columns, like CJK, or none, like combining accents.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Returns the number of terminal columns `s` takes up.
+pub(crate) fn width(s: &str) -> usize {
+    s.width()
+}
+
+/// Returns the number of terminal columns `ch` takes up. Control
+/// characters count as none.
+pub(crate) fn char_width(ch: char) -> usize {
+    ch.width().unwrap_or_default()
+}
+
+/// Returns the columns `skip..skip + take` of `s`. A wide character cut
+/// by the start of the range is replaced by spaces and one cut by its end
+/// is left out, so the result never takes up more than `take` columns.
+pub(crate) fn slice(s: &str, skip: usize, take: usize) -> String {
+    let mut out = String::new();
+    let mut column = 0;
+    for ch in s.chars() {
+        let start = column;
+        column += char_width(ch);
+        if column > skip + take {
+            break;
+        }
+        if start >= skip {
+            out.push(ch);
+        } else if column > skip {
+            out.extend(std::iter::repeat_n(' ', column - skip));
+        }
+    }
+    out
+}
+
+/// Removes the last grapheme cluster from `s`, e.g. a letter together
+/// with its combining accents.
+pub(crate) fn pop_grapheme(s: &mut String) {
+    if let Some((index, _)) = s.grapheme_indices(true).next_back() {
+        s.truncate(index);
+    }
+}
@@ -0,0 +1,176 @@
+//! Styles used to draw the picker on the terminal.
+
+use std::env;
+use crossterm::style::{ContentStyle, Stylize};
+use crate::text;
+
+/// Returns `false` if the user opted out of colors with `NO_COLOR`, or the
+/// terminal is known not to support them.
+pub(crate) fn colors_supported() -> bool {
+    if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+    match env::var("TERM") {
+        Ok(term) => term != "dumb",
+        Err(_) => cfg!(windows),
+    }
+}
+
+/// How the highlighted item stands out from the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum HighlightMode {
+    /// The whole row is highlighted, and the pointer shown before it.
+    #[default]
+    Row,
+    /// Only the pointer marks the highlighted item, which defaults to `>`.
+    Pointer,
+}
+
+/// The strings marking the highlighted and the marked items, and the
+/// highlight mode, as set on the picker.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Indicators {
+    pub(crate) pointer: Option<String>,
+    pub(crate) marker: Option<String>,
+    pub(crate) highlight: HighlightMode,
+}
+
+impl Indicators {
+    /// Returns the pointer shown before the highlighted item, or `default`
+    /// when none was set.
+    pub(crate) fn pointer(&self, default: &str) -> String {
+        match (&self.pointer, self.highlight) {
+            (Some(pointer), _) => one_column(pointer),
+            (None, HighlightMode::Pointer) => String::from(">"),
+            (None, HighlightMode::Row) => String::from(default),
+        }
+    }
+
+    /// Returns the marker shown before items marked in `pick_multi()`.
+    pub(crate) fn marker(&self) -> String {
+        one_column(self.marker.as_deref().unwrap_or("•"))
+    }
+}
+
+/// Fits an indicator into the single column reserved for it, cutting off
+/// the rest or padding it with a space.
+fn one_column(indicator: &str) -> String {
+    let indicator = text::slice(indicator, 0, 1);
+    if indicator.is_empty() { String::from(" ") } else { indicator }
+}
+
+/// The style of each part of the picker.
+pub(crate) struct Theme {
+    pub(crate) prompt: ContentStyle,
+    pub(crate) question: ContentStyle,
+    pub(crate) info: ContentStyle,
+    pub(crate) error: ContentStyle,
+    /// Footer, messages and quick-select numbers.
+    pub(crate) dim: ContentStyle,
+    /// Group and column headers.
+    pub(crate) header: ContentStyle,
+    pub(crate) gutter: ContentStyle,
+    /// Gutter text of the highlighted row.
+    pub(crate) pointer: String,
+    /// Text shown before marked items.
+    pub(crate) mark: String,
+    pub(crate) marker: ContentStyle,
+    pub(crate) selected_marker: ContentStyle,
+    pub(crate) selected: ContentStyle,
+    pub(crate) disabled: ContentStyle,
+    pub(crate) tooltip: ContentStyle,
+}
+
+impl Theme {
+    /// The theme for a terminal, without colors unless it supports them,
+    /// and without relying on colors alone in accessible mode.
+    pub(crate) fn new(colors: bool, accessible: bool, indicators: &Indicators) -> Self {
+        let theme = if colors {
+            Self::color()
+        } else {
+            Self::monochrome()
+        };
+        let theme = if accessible {
+            theme.high_contrast()
+        } else {
+            theme
+        };
+        theme.with_indicators(indicators)
+    }
+
+    /// Applies the pointer, marker and highlight mode chosen for the picker.
+    fn with_indicators(self, indicators: &Indicators) -> Self {
+        let theme = Self {
+            pointer: indicators.pointer(&self.pointer),
+            mark: indicators.marker(),
+            ..self
+        };
+        match indicators.highlight {
+            HighlightMode::Row => theme,
+            HighlightMode::Pointer => Self {
+                selected_marker: theme.marker,
+                selected: ContentStyle::new(),
+                ..theme
+            },
+        }
+    }
+
+    fn color() -> Self {
+        let style = ContentStyle::new();
+        Self {
+            prompt: style.green().bold(),
+            question: style.yellow().bold(),
+            info: style.dark_grey(),
+            error: style.red(),
+            dim: style.dark_grey(),
+            header: style.bold(),
+            gutter: style.on_dark_grey(),
+            pointer: String::from(" "),
+            mark: String::from("•"),
+            marker: style.magenta(),
+            selected_marker: style.magenta().on_dark_grey(),
+            selected: style.white().on_dark_grey(),
+            disabled: style.dark_grey(),
+            tooltip: style.black().on_yellow(),
+        }
+    }
+
+    /// Highlights with bold and underlined text only.
+    fn monochrome() -> Self {
+        let style = ContentStyle::new();
+        Self {
+            prompt: style.bold(),
+            question: style.bold(),
+            info: style,
+            error: style.bold(),
+            dim: style,
+            header: style.bold(),
+            gutter: style,
+            pointer: String::from(">"),
+            mark: String::from("•"),
+            marker: style,
+            selected_marker: style.bold(),
+            selected: style.bold().underlined(),
+            disabled: style.dim(),
+            tooltip: style.reverse(),
+        }
+    }
+
+    /// Replaces gray text and background highlights with a `>` pointer,
+    /// reverse video and text attributes.
+    fn high_contrast(self) -> Self {
+        let style = ContentStyle::new();
+        Self {
+            info: style,
+            dim: style,
+            gutter: style,
+            pointer: String::from(">"),
+            selected_marker: self.marker.bold(),
+            selected: style.reverse().bold(),
+            disabled: style.crossed_out(),
+            tooltip: style.reverse(),
+            ..self
+        }
+    }
+}
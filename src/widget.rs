@@ -0,0 +1,131 @@
+//! Embedding the picker in a ratatui application.
+
+use std::fmt::Display;
+use crossterm::style::{Attribute, ContentStyle};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::Widget;
+use crate::theme::colors_supported;
+use crate::PickerState;
+
+/// A ratatui widget drawing a [`PickerState`] into part of the screen,
+/// returned by [`PickerState::widget`].
+///
+/// The picker adopts the size of the area it is rendered into, so it fits
+/// into the application's own layout instead of taking over the terminal.
+pub struct FuzzyPickerWidget<'a, T: Display + Clone> {
+    picker: &'a mut PickerState<T>,
+}
+
+impl<T: Display + Clone> Widget for FuzzyPickerWidget<'_, T> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.picker.render_into(area, buf);
+    }
+}
+
+impl<T: Display + Clone> PickerState<T> {
+    /// Returns a widget drawing the picker with ratatui, for embedding it
+    /// in an application that owns the terminal. Feed the application's
+    /// events to [`handle_event`](Self::handle_event).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fuzzypicker::{PickOutcome, PickerState};
+    /// use ratatui::{backend::TestBackend, Terminal};
+    ///
+    /// let mut picker = PickerState::new(&["apple", "banana"]);
+    /// let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+    /// loop {
+    ///     terminal.draw(|frame| frame.render_widget(picker.widget(), frame.size())).unwrap();
+    ///     let event = crossterm::event::read().unwrap();
+    ///     if let Some(outcome) = picker.handle_event(&event) {
+    ///         if let PickOutcome::Picked { item, .. } = outcome {
+    ///             println!("{}", item);
+    ///         }
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn widget(&mut self) -> FuzzyPickerWidget<'_, T> {
+        FuzzyPickerWidget { picker: self }
+    }
+
+    /// Fits the picker to `area` and draws it into `buf`.
+    fn render_into(&mut self, area: Rect, buf: &mut Buffer) {
+        self.start();
+        self.update(&mut || false);
+        self.announce_highlight();
+        self.top = area.y;
+        self.left = area.x;
+        self.resize(area.width, area.height);
+        if area.is_empty() {
+            return;
+        }
+        let frame = self.frame(colors_supported());
+        for span in frame.spans {
+            if span.column < self.width {
+                let width = self.width - span.column;
+                buf.set_stringn(area.x + span.column as u16, span.row, span.text, width, ratatui_style(span.style));
+            }
+        }
+        let (column, row) = frame.cursor;
+        buf.get_mut(area.x + column as u16, row).set_style(Style::default().add_modifier(Modifier::REVERSED));
+    }
+}
+
+/// Converts a style of the picker's theme to ratatui's.
+fn ratatui_style(style: ContentStyle) -> Style {
+    let mut converted = Style::default();
+    if let Some(color) = style.foreground_color {
+        converted = converted.fg(ratatui_color(color));
+    }
+    if let Some(color) = style.background_color {
+        converted = converted.bg(ratatui_color(color));
+    }
+    let modifiers = [
+        (Attribute::Bold, Modifier::BOLD),
+        (Attribute::Dim, Modifier::DIM),
+        (Attribute::Italic, Modifier::ITALIC),
+        (Attribute::Underlined, Modifier::UNDERLINED),
+        (Attribute::SlowBlink, Modifier::SLOW_BLINK),
+        (Attribute::RapidBlink, Modifier::RAPID_BLINK),
+        (Attribute::Reverse, Modifier::REVERSED),
+        (Attribute::Hidden, Modifier::HIDDEN),
+        (Attribute::CrossedOut, Modifier::CROSSED_OUT),
+    ];
+    for (attribute, modifier) in modifiers {
+        if style.attributes.has(attribute) {
+            converted = converted.add_modifier(modifier);
+        }
+    }
+    converted
+}
+
+/// Converts a crossterm color to ratatui's, whose names for the normal and
+/// bright variants differ.
+fn ratatui_color(color: crossterm::style::Color) -> Color {
+    use crossterm::style::Color as C;
+    match color {
+        C::Reset => Color::Reset,
+        C::Black => Color::Black,
+        C::DarkRed => Color::Red,
+        C::DarkGreen => Color::Green,
+        C::DarkYellow => Color::Yellow,
+        C::DarkBlue => Color::Blue,
+        C::DarkMagenta => Color::Magenta,
+        C::DarkCyan => Color::Cyan,
+        C::Grey => Color::Gray,
+        C::DarkGrey => Color::DarkGray,
+        C::Red => Color::LightRed,
+        C::Green => Color::LightGreen,
+        C::Yellow => Color::LightYellow,
+        C::Blue => Color::LightBlue,
+        C::Magenta => Color::LightMagenta,
+        C::Cyan => Color::LightCyan,
+        C::White => Color::White,
+        C::Rgb { r, g, b } => Color::Rgb(r, g, b),
+        C::AnsiValue(value) => Color::Indexed(value),
+    }
+}